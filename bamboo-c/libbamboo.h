@@ -71,6 +71,12 @@ typedef enum DecodeError {
   DecodeError_LipmaaError,
   DecodeError_SigError,
   DecodeError_InputIsLengthZero,
+  DecodeError_EndOfFeedTagInvalid,
+  DecodeError_NonCanonicalVaru64,
+  DecodeError_TrailingBytes,
+  DecodeError_EncodingNotCanonical,
+  DecodeError_UnsupportedHashAlgorithm,
+  DecodeError_WeakAuthorKey,
 } DecodeError;
 
 typedef enum PublishError {
@@ -88,6 +94,15 @@ typedef enum PublishError {
   PublishError_DecodeLipmaaEntry,
   PublishError_PublishWithIncorrectBacklinkLogId,
   PublishError_PublishWithIncorrectLipmaaLinkLogId,
+  PublishError_PublishWithInvalidSignatureLength,
+  PublishError_PublishOutBufferTooSmall,
+  PublishError_DecodeSignedEntry,
+  PublishError_PublishWithInvalidSignature,
+  PublishError_PublishSignFailed,
+  PublishError_PublishWithDifferentHashAlgorithm,
+  PublishError_PublishReadPayload,
+  PublishError_PublishWithInvalidPayloadHashLength,
+  PublishError_DecodeAncestorEntry,
 } PublishError;
 
 typedef enum VerifyError {
@@ -110,6 +125,15 @@ typedef enum VerifyError {
   VerifyError_DecodeEntry,
   VerifyError_EncodeEntryForSigning,
   VerifyError_UnknownError,
+  VerifyError_ReadPayload,
+  VerifyError_WeakAuthorKey,
+  VerifyError_WeakSignature,
+  VerifyError_PayloadTooLarge,
+  VerifyError_SeqNumTooLarge,
+  VerifyError_AuthorNotAllowed,
+  VerifyError_LogIdNotAllowed,
+  VerifyError_EndOfFeedNotAllowed,
+  VerifyError_DuplicateSeqNum,
 } VerifyError;
 
 typedef struct CEntry {
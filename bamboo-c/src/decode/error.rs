@@ -15,6 +15,12 @@ pub enum DecodeError {
     LipmaaError,
     SigError,
     InputIsLengthZero,
+    EndOfFeedTagInvalid,
+    NonCanonicalVaru64,
+    TrailingBytes,
+    EncodingNotCanonical,
+    UnsupportedHashAlgorithm,
+    WeakAuthorKey,
 }
 
 impl From<BambooDecodeError> for DecodeError {
@@ -30,6 +36,11 @@ impl From<BambooDecodeError> for DecodeError {
             BambooDecodeError::DecodeLipmaaError { .. } => DecodeError::LipmaaError,
             BambooDecodeError::DecodeSigError { .. } => DecodeError::SigError,
             BambooDecodeError::DecodeInputIsLengthZero => DecodeError::InputIsLengthZero,
+            BambooDecodeError::DecodeEndOfFeedTagInvalid { .. } => DecodeError::EndOfFeedTagInvalid,
+            BambooDecodeError::DecodeNonCanonicalVaru64 { .. } => DecodeError::NonCanonicalVaru64,
+            BambooDecodeError::DecodeTrailingBytes { .. } => DecodeError::TrailingBytes,
+            BambooDecodeError::DecodeEncodingNotCanonical => DecodeError::EncodingNotCanonical,
+            BambooDecodeError::DecodeWeakAuthorKey => DecodeError::WeakAuthorKey,
         }
     }
 }
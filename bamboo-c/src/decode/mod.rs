@@ -1,6 +1,6 @@
 use bamboo_rs_core::entry::decode;
 use bamboo_rs_core::signature::ED25519_SIGNATURE_SIZE;
-use bamboo_rs_core::yamf_hash::{YamfHash, BLAKE2B_HASH_SIZE};
+use bamboo_rs_core::yamf_hash::{HashAlgorithm, BLAKE2B_HASH_SIZE};
 use core::slice;
 use ed25519_dalek::PUBLIC_KEY_LENGTH;
 
@@ -32,7 +32,8 @@ pub struct DecodeEd25519Blade2bEntryArgs<'a> {
 /// Attempts to decode bytes as an entry.
 ///
 /// Returns `Error` which will have a value of `0` if decoding was
-/// successful.
+/// successful. Entries that use a hash other than blake2b are rejected with
+/// `UnsupportedHashAlgorithm`.
 #[no_mangle]
 pub extern "C" fn decode_ed25519_blake2b_entry(
     args: &mut DecodeEd25519Blade2bEntryArgs,
@@ -42,6 +43,20 @@ pub extern "C" fn decode_ed25519_blake2b_entry(
     decode(&entry_slice)
         .map_err(|err| err.into())
         .and_then::<(), _>(|entry| {
+            let is_blake2b = |algorithm| algorithm == HashAlgorithm::Blake2b;
+            if !is_blake2b(entry.payload_hash.algorithm())
+                || !entry
+                    .lipmaa_link
+                    .iter()
+                    .all(|link| is_blake2b(link.algorithm()))
+                || !entry
+                    .backlink
+                    .iter()
+                    .all(|link| is_blake2b(link.algorithm()))
+            {
+                return Err(DecodeError::UnsupportedHashAlgorithm);
+            }
+
             args.out_decoded_entry.log_id = entry.log_id;
             args.out_decoded_entry.is_end_of_feed = entry.is_end_of_feed;
             args.out_decoded_entry.seq_num = entry.seq_num;
@@ -53,23 +68,16 @@ pub extern "C" fn decode_ed25519_blake2b_entry(
                 args.out_decoded_entry.sig[..].copy_from_slice(&sig.0[..]);
             });
 
-            entry.lipmaa_link.map(|lipmaa_link| match lipmaa_link {
-                YamfHash::Blake2b(bytes) => {
-                    args.out_decoded_entry.lipmaa_link[..].copy_from_slice(&bytes[..]);
-                }
+            entry.lipmaa_link.map(|lipmaa_link| {
+                args.out_decoded_entry.lipmaa_link[..].copy_from_slice(lipmaa_link.as_bytes());
             });
 
-            entry.backlink.map(|backlink| match backlink {
-                YamfHash::Blake2b(bytes) => {
-                    args.out_decoded_entry.backlink[..].copy_from_slice(&bytes[..]);
-                }
+            entry.backlink.map(|backlink| {
+                args.out_decoded_entry.backlink[..].copy_from_slice(backlink.as_bytes());
             });
 
-            match entry.payload_hash {
-                YamfHash::Blake2b(bytes) => {
-                    args.out_decoded_entry.payload_hash_bytes[..].copy_from_slice(&bytes[..]);
-                }
-            };
+            args.out_decoded_entry.payload_hash_bytes[..]
+                .copy_from_slice(entry.payload_hash.as_bytes());
 
             args.out_decoded_entry.author[..].copy_from_slice(&entry.author.as_bytes()[..]);
 
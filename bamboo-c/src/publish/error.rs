@@ -18,6 +18,15 @@ pub enum PublishError {
     DecodeLipmaaEntry,
     PublishWithIncorrectBacklinkLogId,
     PublishWithIncorrectLipmaaLinkLogId,
+    PublishWithInvalidSignatureLength,
+    PublishOutBufferTooSmall,
+    DecodeSignedEntry,
+    PublishWithInvalidSignature,
+    PublishSignFailed,
+    PublishWithDifferentHashAlgorithm,
+    PublishReadPayload,
+    PublishWithInvalidPayloadHashLength,
+    DecodeAncestorEntry,
 }
 
 impl From<BambooPublishError> for PublishError {
@@ -48,6 +57,25 @@ impl From<BambooPublishError> for PublishError {
             BambooPublishError::PublishKeypairDidNotMatchLipmaaLinkPublicKey => {
                 PublishError::PublishKeypairDidNotMatchLipmaaLinkPublicKey
             }
+            BambooPublishError::PublishWithInvalidSignatureLength { .. } => {
+                PublishError::PublishWithInvalidSignatureLength
+            }
+            BambooPublishError::PublishOutBufferTooSmall { .. } => {
+                PublishError::PublishOutBufferTooSmall
+            }
+            BambooPublishError::DecodeSignedEntry { .. } => PublishError::DecodeSignedEntry,
+            BambooPublishError::PublishWithInvalidSignature { .. } => {
+                PublishError::PublishWithInvalidSignature
+            }
+            BambooPublishError::PublishSignFailed => PublishError::PublishSignFailed,
+            BambooPublishError::PublishWithDifferentHashAlgorithm { .. } => {
+                PublishError::PublishWithDifferentHashAlgorithm
+            }
+            BambooPublishError::PublishReadPayload { .. } => PublishError::PublishReadPayload,
+            BambooPublishError::PublishWithInvalidPayloadHashLength { .. } => {
+                PublishError::PublishWithInvalidPayloadHashLength
+            }
+            BambooPublishError::DecodeAncestorEntry { .. } => PublishError::DecodeAncestorEntry,
         }
     }
 }
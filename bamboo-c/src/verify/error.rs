@@ -23,6 +23,15 @@ pub enum VerifyError {
     DecodeEntry,
     EncodeEntryForSigning,
     UnknownError,
+    ReadPayload,
+    WeakAuthorKey,
+    WeakSignature,
+    PayloadTooLarge,
+    SeqNumTooLarge,
+    AuthorNotAllowed,
+    LogIdNotAllowed,
+    EndOfFeedNotAllowed,
+    DuplicateSeqNum,
 }
 
 impl From<BambooVerifyError> for VerifyError {
@@ -58,6 +67,15 @@ impl From<BambooVerifyError> for VerifyError {
                 VerifyError::BacklinkAuthorDoesNotMatch
             }
             BambooVerifyError::UnknownError => VerifyError::UnknownError,
+            BambooVerifyError::ReadPayload { .. } => VerifyError::ReadPayload,
+            BambooVerifyError::WeakAuthorKey => VerifyError::WeakAuthorKey,
+            BambooVerifyError::WeakSignature => VerifyError::WeakSignature,
+            BambooVerifyError::PayloadTooLarge { .. } => VerifyError::PayloadTooLarge,
+            BambooVerifyError::SeqNumTooLarge { .. } => VerifyError::SeqNumTooLarge,
+            BambooVerifyError::AuthorNotAllowed => VerifyError::AuthorNotAllowed,
+            BambooVerifyError::LogIdNotAllowed { .. } => VerifyError::LogIdNotAllowed,
+            BambooVerifyError::EndOfFeedNotAllowed => VerifyError::EndOfFeedNotAllowed,
+            BambooVerifyError::DuplicateSeqNum { .. } => VerifyError::DuplicateSeqNum,
         }
     }
 }
@@ -0,0 +1,105 @@
+#[cfg(test)]
+mod tests {
+    use arbitrary::{Arbitrary, Unstructured};
+    use bamboo_rs_core::arbitrary::{ArbitraryLog, CorruptedEntry, Corruption};
+    use bamboo_rs_core::entry::decode::Error as DecodeError;
+    use bamboo_rs_core::entry::verify::Error as VerifyError;
+    use bamboo_rs_core::entry::{decode, verify, OwnedEntry, MAX_ENTRY_SIZE};
+    use rand::{rngs::OsRng, RngCore};
+
+    // Random fuzzer input, plus no input at all which makes every choice its default.
+    fn inputs() -> Vec<Vec<u8>> {
+        let mut inputs = vec![Vec::new()];
+        for _ in 0..50 {
+            let mut bytes = vec![0u8; 4096];
+            OsRng.fill_bytes(&mut bytes);
+            inputs.push(bytes);
+        }
+        inputs
+    }
+
+    #[test]
+    fn arbitrary_entries_decode_and_have_valid_signatures() {
+        for input in inputs() {
+            let entry = OwnedEntry::arbitrary(&mut Unstructured::new(&input)).unwrap();
+
+            let mut out = [0u8; MAX_ENTRY_SIZE];
+            let size = entry.as_entry().encode(&mut out).unwrap();
+            let decoded = decode(&out[..size]).unwrap();
+
+            assert_eq!(decoded, entry.as_entry());
+            assert!(decoded.verify_signature().is_ok());
+        }
+    }
+
+    #[test]
+    fn arbitrary_logs_verify() {
+        for input in inputs() {
+            let mut u = Unstructured::new(&input);
+            let log = ArbitraryLog::arbitrary(&mut u).unwrap();
+            assert!(!log.entries.is_empty());
+
+            for seq_num in 1..=log.entries.len() as u64 {
+                let entry = log.entry(seq_num).unwrap();
+                let result = verify(
+                    entry,
+                    log.payload(seq_num),
+                    log.lipmaa_entry(seq_num),
+                    log.backlink_entry(seq_num),
+                );
+
+                assert!(result.is_ok(), "seq_num {}: {:?}", seq_num, result);
+                assert_eq!(decode(entry).unwrap().seq_num, seq_num);
+            }
+        }
+    }
+
+    #[test]
+    fn arbitrary_log_with_len_has_len_entries() {
+        let input = inputs().pop().unwrap();
+        let log = ArbitraryLog::with_len(&mut Unstructured::new(&input), 20).unwrap();
+
+        assert_eq!(log.entries.len(), 20);
+        assert_eq!(log.payloads.len(), 20);
+        assert!(log.entry(21).is_none());
+    }
+
+    #[test]
+    fn corrupted_entries_fail_with_their_error() {
+        for input in inputs() {
+            for corruption in Corruption::ALL.iter() {
+                let mut u = Unstructured::new(&input);
+                let corrupted = CorruptedEntry::with_corruption(&mut u, *corruption).unwrap();
+                let error = corrupted.verify().unwrap_err();
+
+                assert!(
+                    corruption.matches(&error),
+                    "{:?} failed with {:?}",
+                    corruption,
+                    error
+                );
+            }
+
+            let corrupted = CorruptedEntry::arbitrary(&mut Unstructured::new(&input)).unwrap();
+            assert!(corrupted
+                .corruption
+                .matches(&corrupted.verify().unwrap_err()));
+        }
+    }
+
+    #[test]
+    fn decode_signature_corruption_fails_decoding_the_signature() {
+        for input in inputs() {
+            let mut u = Unstructured::new(&input);
+            let corrupted =
+                CorruptedEntry::with_corruption(&mut u, Corruption::DecodeSignature).unwrap();
+
+            match corrupted.verify() {
+                Err(VerifyError::DecodeEntry {
+                    source: DecodeError::DecodeSigError { .. },
+                }) => {}
+                other => panic!("{:?}", other),
+            }
+        }
+    }
+}
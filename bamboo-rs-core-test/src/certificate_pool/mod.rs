@@ -0,0 +1,109 @@
+#[cfg(test)]
+mod tests {
+    use bamboo_rs_core::certificate_pool::Error;
+    use bamboo_rs_core::{certificate_pool, lipmaa, lipmaa_path};
+    use std::collections::{HashSet, VecDeque};
+
+    #[test]
+    fn certificate_pool_of_small_seq_nums() {
+        let pool = |seq_num| certificate_pool(seq_num).unwrap().collect::<Vec<_>>();
+
+        assert_eq!(pool(1), [1]);
+        assert_eq!(pool(2), [4, 3, 2, 1]);
+        assert_eq!(pool(4), [4, 1]);
+        assert_eq!(pool(6), [13, 12, 8, 7, 6, 5, 4, 1]);
+        assert_eq!(pool(13), [13, 4, 1]);
+    }
+
+    #[test]
+    fn lipmaa_path_only_follows_links() {
+        for from in 1..200 {
+            for to in 1..=from {
+                let path: Vec<u64> = lipmaa_path(from, to).unwrap().collect();
+
+                assert_eq!(path.first(), Some(&from));
+                assert_eq!(path.last(), Some(&to));
+                path.windows(2).for_each(|step| {
+                    assert!(step[1] == step[0] - 1 || step[1] == lipmaa(step[0]));
+                });
+            }
+        }
+    }
+
+    #[test]
+    fn lipmaa_path_is_shortest() {
+        let max = 150;
+
+        for from in 1..max {
+            // Breadth first search over backlinks and lipmaa links.
+            let mut distances = vec![None; max as usize];
+            let mut queue = VecDeque::new();
+            distances[from as usize] = Some(0);
+            queue.push_back(from);
+
+            while let Some(seq_num) = queue.pop_front() {
+                let distance = distances[seq_num as usize].unwrap();
+                for &next in &[seq_num - 1, lipmaa(seq_num)] {
+                    if next > 0 && distances[next as usize].is_none() {
+                        distances[next as usize] = Some(distance + 1);
+                        queue.push_back(next);
+                    }
+                }
+            }
+
+            for to in 1..=from {
+                let length = lipmaa_path(from, to).unwrap().count() - 1;
+                assert_eq!(Some(length), distances[to as usize], "{} to {}", from, to);
+            }
+        }
+    }
+
+    #[test]
+    fn certificate_pools_intersect() {
+        let pools: Vec<HashSet<u64>> = (1..300)
+            .map(|seq_num| certificate_pool(seq_num).unwrap().collect())
+            .collect();
+        let pool = |seq_num: u64| &pools[seq_num as usize - 1];
+        // Whether the pool of `seq_num` holds the whole path between it and `other`.
+        let path_in_pool = |seq_num: u64, other: u64| {
+            lipmaa_path(seq_num.max(other), seq_num.min(other))
+                .unwrap()
+                .all(|on_path| pool(seq_num).contains(&on_path))
+        };
+
+        // Every pool has entry 1, so that alone proves nothing. The pools of `older` and `newer`
+        // must share an entry at least as new as `older`, with the paths from it to both of them.
+        for newer in 1..300 {
+            for older in 1..=newer {
+                let joined = pool(older).intersection(pool(newer)).any(|&shared| {
+                    shared >= older && path_in_pool(older, shared) && path_in_pool(newer, shared)
+                });
+                assert!(joined, "{} and {}", older, newer);
+            }
+        }
+    }
+
+    #[test]
+    fn certificate_pool_of_largest_seq_num() {
+        let pool: Vec<u64> = certificate_pool(u64::MAX).unwrap().collect();
+
+        assert_eq!(pool.first(), Some(&u64::MAX));
+        assert_eq!(pool.last(), Some(&1));
+    }
+
+    #[test]
+    fn invalid_seq_nums_error() {
+        match certificate_pool(0) {
+            Err(Error::ZeroSeqNum) => {}
+            other => panic!("{:?}", other),
+        }
+        match lipmaa_path(5, 0) {
+            Err(Error::ZeroSeqNum) => {}
+            other => panic!("{:?}", other),
+        }
+        match lipmaa_path(5, 6) {
+            Err(Error::PathToNewerEntry { from: 5, to: 6 }) => {}
+            other => panic!("{:?}", other),
+        }
+    }
+}
@@ -0,0 +1,126 @@
+#[cfg(test)]
+mod tests {
+    use bamboo_rs_core::cid::{
+        cid_from_yamf_hash, entry_cid, payload_cid, Cid, Error, Multihash,
+        BLAKE2B_512_MULTIHASH_CODE, RAW_CODEC,
+    };
+    use bamboo_rs_core::entry::{publish_with_hash_algorithm, MAX_ENTRY_SIZE};
+    use bamboo_rs_core::yamf_hash::{new_blake2b, new_blake3};
+    use bamboo_rs_core::{decode, HashAlgorithm, Keypair, YamfHash};
+    use rand::rngs::OsRng;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn yamf_hash_round_trips_through_multihash() {
+        for hash in [new_blake2b(b"hello"), new_blake3(b"hello")].iter() {
+            let multihash = Multihash::from(hash);
+
+            assert_eq!(multihash.code(), hash.algorithm().multihash_code());
+            assert_eq!(multihash.digest(), hash.as_bytes());
+            assert_eq!(YamfHash::try_from(&multihash).unwrap(), *hash);
+        }
+    }
+
+    #[test]
+    fn blake2b_multihash_has_the_registered_prefix() {
+        let multihash = Multihash::from(&new_blake2b(b"hello"));
+        let bytes = multihash.to_bytes();
+
+        assert_eq!(bytes[..4], [0xc0, 0xe4, 0x02, 0x40]);
+        assert_eq!(bytes[4..], *new_blake2b(b"hello").as_bytes());
+    }
+
+    #[test]
+    fn unsupported_multihashes_error() {
+        let sha2_256 = Multihash::<64>::wrap(0x12, &[0; 32]).unwrap();
+        let short_blake2b = Multihash::<64>::wrap(BLAKE2B_512_MULTIHASH_CODE, &[0; 32]).unwrap();
+
+        assert!(matches!(
+            YamfHash::try_from(&sha2_256),
+            Err(Error::UnsupportedMultihashCode { code: 0x12 })
+        ));
+        assert!(matches!(
+            YamfHash::try_from(&short_blake2b),
+            Err(Error::InvalidDigestLength {
+                length: 32,
+                expected: 64
+            })
+        ));
+    }
+
+    #[test]
+    fn payload_cid_round_trips_through_string() {
+        let cid = payload_cid(b"hello", HashAlgorithm::Blake2b);
+        let parsed: Cid = cid.to_string().parse().unwrap();
+
+        assert_eq!(parsed, cid);
+        assert_eq!(parsed.codec(), RAW_CODEC);
+        assert_eq!(
+            YamfHash::try_from(parsed.hash()).unwrap(),
+            new_blake2b(b"hello")
+        );
+    }
+
+    #[test]
+    fn entry_cid_matches_the_backlink_to_it() {
+        let mut csprng: OsRng = OsRng {};
+        let key_pair: Keypair = Keypair::generate(&mut csprng);
+
+        for hash_algorithm in [HashAlgorithm::Blake2b, HashAlgorithm::Blake3].iter() {
+            let mut first = [0u8; MAX_ENTRY_SIZE];
+            let first_size = publish_with_hash_algorithm(
+                &mut first,
+                &key_pair,
+                *hash_algorithm,
+                0,
+                b"first",
+                false,
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+
+            let mut second = [0u8; MAX_ENTRY_SIZE];
+            let second_size = publish_with_hash_algorithm(
+                &mut second,
+                &key_pair,
+                *hash_algorithm,
+                0,
+                b"second",
+                false,
+                Some(1),
+                Some(&first[..first_size]),
+                Some(&first[..first_size]),
+            )
+            .unwrap();
+
+            let backlink = decode(&second[..second_size]).unwrap().backlink.unwrap();
+
+            assert_eq!(
+                entry_cid(&first[..first_size]).unwrap(),
+                cid_from_yamf_hash(&backlink)
+            );
+        }
+
+        assert!(matches!(entry_cid(&[]), Err(Error::DecodeCidEntry { .. })));
+
+        let mut out = [0u8; MAX_ENTRY_SIZE];
+        let size = publish_with_hash_algorithm(
+            &mut out,
+            &key_pair,
+            HashAlgorithm::Blake2b,
+            0,
+            b"first",
+            false,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        assert!(matches!(
+            entry_cid(&out[..size + 2]),
+            Err(Error::CidEntryHasTrailingBytes { length: 2 })
+        ));
+    }
+}
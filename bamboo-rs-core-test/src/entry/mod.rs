@@ -1,16 +1,52 @@
 #[cfg(test)]
 mod tests {
     use bamboo_rs_core::entry::decode;
+    use bamboo_rs_core::entry::decode::{decode_with_length, Error as DecodeError, ReadError};
+    use bamboo_rs_core::entry::inclusion_proof::Error as InclusionProofError;
+    use bamboo_rs_core::entry::owned::Error as OwnedEntryError;
     use bamboo_rs_core::entry::publish::Error as PublishError;
-    use bamboo_rs_core::entry::verify::batch::verify_batch_signatures;
-    use bamboo_rs_core::entry::verify::Error as VerifyError;
-    use bamboo_rs_core::entry::verify_batch;
+    use bamboo_rs_core::entry::verify::batch::{
+        verify_batch_signatures, verify_batch_signatures_report,
+    };
+    use bamboo_rs_core::entry::verify::sequential::{
+        verify_batch_sequential, verify_batch_sequential_strict,
+        verify_batch_sequential_with_policy,
+    };
+    use bamboo_rs_core::entry::verify::{
+        is_weak_key, verify_batch_multi_feed_with_policy, verify_batch_signatures_strict,
+        Error as VerifyError, OwnedVerifyPolicy, VerifiedChecks,
+    };
+    use bamboo_rs_core::entry::{
+        attach_signature, decode_many, decode_strict, inclusion_proof, publish_from_reader,
+        publish_many, publish_unsigned, publish_unsigned_with_hash_algorithm,
+        publish_with_hash_algorithm, publish_with_links, publish_with_payload_hash, verify_entry,
+        verify_from_reader, verify_inclusion_proof, verify_strict, verify_with_payload_hash,
+        verify_with_policy, EntryBuilder, EntryReader, EntrySigner, OwnedEntry, VerifyPolicy,
+    };
+    use bamboo_rs_core::entry::{
+        verify_batch, verify_batch_multi_feed, verify_batch_report, verify_batch_report_strict,
+        verify_batch_report_with_policy, verify_batch_strict, verify_batch_with_policy,
+    };
     use bamboo_rs_core::signature::ED25519_SIGNATURE_SIZE;
-    use bamboo_rs_core::yamf_hash::BLAKE2B_HASH_SIZE;
-    use bamboo_rs_core::{publish, verify, Entry, Signature, YamfHash};
-    use ed25519_dalek::{Keypair, PublicKey};
+    use bamboo_rs_core::yamf_hash::{
+        new_blake2b, new_blake3, Error as YamfHashError, HashAlgorithm, BLAKE2B_HASH_SIZE,
+        BLAKE3_HASH_SIZE,
+    };
+    use bamboo_rs_core::{
+        lipmaa, lipmaa_path, publish, verify, Entry, Signature, YamfHash, YamfHasher,
+    };
+    use curve25519_dalek::constants::{ED25519_BASEPOINT_TABLE, EIGHT_TORSION};
+    use curve25519_dalek::edwards::CompressedEdwardsY;
+    use curve25519_dalek::scalar::Scalar;
+    use curve25519_dalek::traits::IsIdentity;
+    use ed25519_dalek::{
+        Keypair, PublicKey, Signature as DalekSignature, SignatureError, Signer, PUBLIC_KEY_LENGTH,
+    };
     use rand::rngs::OsRng;
-    use std::io::Write;
+    use std::cell::RefCell;
+    use std::collections::HashSet;
+    use std::convert::TryFrom;
+    use std::io::{self, Cursor, Read, Write};
     use varu64::encode_write as varu64_encode_write;
 
     #[test]
@@ -45,6 +81,7 @@ mod tests {
             YamfHash::Blake2b(ref hash) => {
                 assert_eq!(hash.as_ref(), &payload_hash_bytes[..]);
             }
+            _ => panic!(),
         }
 
         match entry.backlink {
@@ -527,4 +564,2034 @@ mod tests {
             err => panic!("{:?}", err),
         }
     }
+
+    fn three_published_entries() -> Vec<u8> {
+        let mut csprng: OsRng = OsRng {};
+        let key_pair: Keypair = Keypair::generate(&mut csprng);
+
+        let payload = "hello bamboo!";
+        let mut out = [0u8; 512];
+        let size = publish(
+            &mut out,
+            &key_pair,
+            0,
+            payload.as_bytes(),
+            false,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+
+        let mut out2 = [0u8; 512];
+        let size2 = publish(
+            &mut out2,
+            &key_pair,
+            0,
+            payload.as_bytes(),
+            false,
+            Some(1),
+            Some(&out[..size]),
+            Some(&out[..size]),
+        )
+        .unwrap();
+
+        let mut out3 = [0u8; 512];
+        let size3 = publish(
+            &mut out3,
+            &key_pair,
+            0,
+            payload.as_bytes(),
+            false,
+            Some(2),
+            Some(&out[..size]),
+            Some(&out2[..size2]),
+        )
+        .unwrap();
+
+        [&out[..size], &out2[..size2], &out3[..size3]].concat()
+    }
+
+    #[test]
+    fn decode_with_length_ignores_trailing_bytes() {
+        let bytes = three_published_entries();
+        let (first_entry, length) = decode_with_length(&bytes).unwrap();
+
+        assert_eq!(length, first_entry.encoding_length());
+        assert_eq!(decode(&bytes[..length]).unwrap(), first_entry);
+    }
+
+    #[test]
+    fn decode_many_entries() {
+        let bytes = three_published_entries();
+
+        let decoded = decode_many(&bytes).collect::<Result<Vec<_>, _>>().unwrap();
+
+        assert_eq!(decoded.len(), 3);
+        assert_eq!(decoded[0].range.start, 0);
+        assert_eq!(decoded[2].range.end, bytes.len());
+
+        decoded.iter().enumerate().for_each(|(i, decoded_entry)| {
+            assert_eq!(decoded_entry.entry.seq_num, i as u64 + 1);
+            assert_eq!(decoded_entry.bytes, &bytes[decoded_entry.range.clone()]);
+            assert_eq!(decode(decoded_entry.bytes).unwrap(), decoded_entry.entry);
+        });
+    }
+
+    #[test]
+    fn decode_many_errors_on_trailing_bytes_then_stops() {
+        let mut bytes = three_published_entries();
+        let valid_length = bytes.len();
+        bytes.extend_from_slice(&[0u8; 10]);
+
+        let mut iter = decode_many(&bytes);
+
+        assert!(iter.by_ref().take(3).all(|result| result.is_ok()));
+        assert_eq!(iter.consumed(), valid_length);
+
+        match iter.next() {
+            Some(Err(DecodeError::DecodeAuthorError)) => {}
+            other => panic!("{:?}", other),
+        }
+        assert!(iter.next().is_none());
+        assert_eq!(iter.remaining(), &[0u8; 10]);
+    }
+
+    #[test]
+    fn entry_reader_reads_entries() {
+        let bytes = three_published_entries();
+        let mut reader = EntryReader::new(Cursor::new(bytes.clone()));
+
+        let read_entries = reader.by_ref().collect::<Result<Vec<_>, _>>().unwrap();
+
+        assert_eq!(read_entries.len(), 3);
+        assert_eq!(reader.consumed(), bytes.len() as u64);
+
+        read_entries.iter().enumerate().for_each(|(i, read_entry)| {
+            let start = read_entry.range.start as usize;
+            let end = read_entry.range.end as usize;
+            assert_eq!(read_entry.bytes, &bytes[start..end]);
+            assert_eq!(read_entry.entry().seq_num, i as u64 + 1);
+        });
+    }
+
+    #[test]
+    fn entry_reader_errors_on_truncated_entry() {
+        let mut bytes = three_published_entries();
+        bytes.pop();
+
+        let mut reader = EntryReader::new(Cursor::new(bytes));
+
+        assert!(reader.next().unwrap().is_ok());
+        assert!(reader.next().unwrap().is_ok());
+
+        match reader.next() {
+            Some(Err(ReadError::ReadEntryDecode {
+                source: DecodeError::DecodeSigError { .. },
+                ..
+            })) => {}
+            other => panic!("{:?}", other),
+        }
+        assert!(reader.next().is_none());
+    }
+
+    fn first_published_entry() -> Vec<u8> {
+        let mut csprng: OsRng = OsRng {};
+        let key_pair: Keypair = Keypair::generate(&mut csprng);
+
+        let payload = "hello bamboo!";
+        let mut out = [0u8; 512];
+        let size = publish(
+            &mut out,
+            &key_pair,
+            0,
+            payload.as_bytes(),
+            false,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+
+        out[..size].to_vec()
+    }
+
+    #[test]
+    fn decode_strict_accepts_published_entries() {
+        let bytes = three_published_entries();
+
+        decode_many(&bytes).for_each(|decoded_entry| {
+            let decoded_entry = decoded_entry.unwrap();
+            assert_eq!(
+                decode_strict(decoded_entry.bytes).unwrap(),
+                decoded_entry.entry
+            );
+        });
+    }
+
+    #[test]
+    fn decode_strict_rejects_invalid_end_of_feed_tag() {
+        let mut bytes = first_published_entry();
+        bytes[0] = 2;
+
+        assert!(!decode(&bytes).unwrap().is_end_of_feed);
+
+        match decode_strict(&bytes) {
+            Err(DecodeError::DecodeEndOfFeedTagInvalid { tag: 2 }) => {}
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn decode_strict_rejects_trailing_bytes() {
+        let mut bytes = first_published_entry();
+        bytes.push(0);
+
+        assert!(decode(&bytes).is_ok());
+
+        match decode_strict(&bytes) {
+            Err(DecodeError::DecodeTrailingBytes { length: 1 }) => {}
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn decode_strict_rejects_non_canonical_varu64() {
+        let bytes = first_published_entry();
+        // The log_id follows the tag byte and the author. Re-encode it as a two byte varu64.
+        let log_id_index = 1 + 32;
+        assert_eq!(bytes[log_id_index], 0);
+        let bytes = [
+            &bytes[..log_id_index],
+            &[248, 0][..],
+            &bytes[log_id_index + 1..],
+        ]
+        .concat();
+
+        match decode(&bytes) {
+            Err(DecodeError::DecodeLogIdError) => {}
+            other => panic!("{:?}", other),
+        }
+
+        match decode_strict(&bytes) {
+            Err(DecodeError::DecodeNonCanonicalVaru64 {
+                field: "log_id",
+                value: 0,
+            }) => {}
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn decode_rejects_wrong_payload_hash_size() {
+        let mut bytes = first_published_entry();
+        // The payload hash size follows the tag byte, author, log_id, seq_num, payload_size and
+        // the yamf hash id.
+        let payload_hash_size_index = 1 + 32 + 1 + 1 + 1 + 1;
+        assert_eq!(bytes[payload_hash_size_index], BLAKE2B_HASH_SIZE as u8);
+        bytes[payload_hash_size_index] = 63;
+
+        match decode(&bytes) {
+            Err(DecodeError::DecodePayloadHashError {
+                source: YamfHashError::DecodeLengthError,
+            }) => {}
+            other => panic!("{:?}", other),
+        }
+
+        match decode_strict(&bytes) {
+            Err(DecodeError::DecodePayloadHashError {
+                source: YamfHashError::DecodeLengthError,
+            }) => {}
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn publish_unsigned_then_attach_signature_matches_publish() {
+        let mut csprng: OsRng = OsRng {};
+        let key_pair: Keypair = Keypair::generate(&mut csprng);
+
+        let payload = "hello bamboo!";
+        let mut out = [0u8; 512];
+        let size = publish(
+            &mut out,
+            &key_pair,
+            0,
+            payload.as_bytes(),
+            false,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+
+        let mut expected = [0u8; 512];
+        let expected_size = publish(
+            &mut expected,
+            &key_pair,
+            0,
+            payload.as_bytes(),
+            false,
+            Some(1),
+            Some(&out[..size]),
+            Some(&out[..size]),
+        )
+        .unwrap();
+
+        let mut out2 = [0u8; 512];
+        let unsigned_size = publish_unsigned(
+            &mut out2,
+            &key_pair.public,
+            0,
+            payload.as_bytes(),
+            false,
+            Some(1),
+            Some(&out[..size]),
+            Some(&out[..size]),
+        )
+        .unwrap();
+
+        // This would happen in some other process that holds the secret key.
+        let signature = key_pair.sign(&out2[..unsigned_size]).to_bytes();
+
+        let size2 = attach_signature(&mut out2, unsigned_size, &signature).unwrap();
+
+        assert_eq!(&out2[..size2], &expected[..expected_size]);
+        assert!(verify(
+            &out2[..size2],
+            Some(payload.as_bytes()),
+            Some(&out[..size]),
+            Some(&out[..size])
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn publish_unsigned_checks_links() {
+        let mut csprng: OsRng = OsRng {};
+        let key_pair: Keypair = Keypair::generate(&mut csprng);
+        let payload = "hello bamboo!";
+
+        let mut out = [0u8; 512];
+        match publish_unsigned(
+            &mut out,
+            &key_pair.public,
+            0,
+            payload.as_bytes(),
+            false,
+            Some(1),
+            None,
+            None,
+        ) {
+            Err(PublishError::PublishWithoutBacklinkEntry) => {}
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn publish_unsigned_with_hash_algorithm_publishes_to_a_blake3_log() {
+        let mut csprng: OsRng = OsRng {};
+        let key_pair: Keypair = Keypair::generate(&mut csprng);
+
+        let entries = publish_entries(&key_pair, HashAlgorithm::Blake3, 0, 1);
+        let payload = "hello bamboo!";
+
+        let mut out = [0u8; 512];
+        let unsigned_size = publish_unsigned_with_hash_algorithm(
+            &mut out,
+            &key_pair.public,
+            HashAlgorithm::Blake3,
+            0,
+            payload.as_bytes(),
+            false,
+            Some(1),
+            Some(&entries[0]),
+            Some(&entries[0]),
+        )
+        .unwrap();
+
+        let signature = key_pair.sign(&out[..unsigned_size]).to_bytes();
+        let size = attach_signature(&mut out, unsigned_size, &signature).unwrap();
+
+        verify(
+            &out[..size],
+            Some(payload.as_bytes()),
+            Some(&entries[0]),
+            Some(&entries[0]),
+        )
+        .unwrap();
+
+        // The links are checked to use the same algorithm, like publish_with_hash_algorithm.
+        match publish_unsigned(
+            &mut out,
+            &key_pair.public,
+            0,
+            payload.as_bytes(),
+            false,
+            Some(1),
+            Some(&entries[0]),
+            Some(&entries[0]),
+        ) {
+            Err(PublishError::PublishWithDifferentHashAlgorithm {
+                expected: HashAlgorithm::Blake2b,
+                actual: HashAlgorithm::Blake3,
+            }) => {}
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn attach_signature_rejects_signature_from_another_key() {
+        let mut csprng: OsRng = OsRng {};
+        let key_pair: Keypair = Keypair::generate(&mut csprng);
+        let other_key_pair: Keypair = Keypair::generate(&mut csprng);
+
+        let payload = "hello bamboo!";
+        let mut out = [0u8; 512];
+        let unsigned_size = publish_unsigned(
+            &mut out,
+            &key_pair.public,
+            0,
+            payload.as_bytes(),
+            false,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+
+        let signature = other_key_pair.sign(&out[..unsigned_size]).to_bytes();
+
+        match attach_signature(&mut out, unsigned_size, &signature) {
+            Err(PublishError::PublishWithInvalidSignature {
+                source: VerifyError::InvalidSignature,
+            }) => {}
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn attach_signature_checks_lengths() {
+        let mut csprng: OsRng = OsRng {};
+        let key_pair: Keypair = Keypair::generate(&mut csprng);
+
+        let payload = "hello bamboo!";
+        let mut out = [0u8; 512];
+        let unsigned_size = publish_unsigned(
+            &mut out,
+            &key_pair.public,
+            0,
+            payload.as_bytes(),
+            false,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+
+        let signature = key_pair.sign(&out[..unsigned_size]).to_bytes();
+
+        match attach_signature(&mut out, unsigned_size, &signature[..32]) {
+            Err(PublishError::PublishWithInvalidSignatureLength { length: 32 }) => {}
+            other => panic!("{:?}", other),
+        }
+
+        match attach_signature(&mut out[..unsigned_size], unsigned_size, &signature) {
+            Err(PublishError::PublishOutBufferTooSmall { .. }) => {}
+            other => panic!("{:?}", other),
+        }
+    }
+
+    // Test double that signs with a keypair and remembers every message it signed.
+    struct RecordingSigner {
+        key_pair: Keypair,
+        signed: RefCell<Vec<Vec<u8>>>,
+    }
+
+    impl Signer<DalekSignature> for RecordingSigner {
+        fn try_sign(&self, msg: &[u8]) -> Result<DalekSignature, SignatureError> {
+            self.signed.borrow_mut().push(msg.to_vec());
+            self.key_pair.try_sign(msg)
+        }
+    }
+
+    impl EntrySigner for RecordingSigner {
+        fn public_key(&self) -> PublicKey {
+            self.key_pair.public
+        }
+    }
+
+    // Test double for a keystore that refuses to sign.
+    struct FailingSigner(PublicKey);
+
+    impl Signer<DalekSignature> for FailingSigner {
+        fn try_sign(&self, _msg: &[u8]) -> Result<DalekSignature, SignatureError> {
+            Err(SignatureError::new())
+        }
+    }
+
+    impl EntrySigner for FailingSigner {
+        fn public_key(&self) -> PublicKey {
+            self.0
+        }
+    }
+
+    #[test]
+    fn publish_with_custom_signer() {
+        let mut csprng: OsRng = OsRng {};
+        let signer = RecordingSigner {
+            key_pair: Keypair::generate(&mut csprng),
+            signed: RefCell::new(Vec::new()),
+        };
+
+        let payload = "hello bamboo!";
+        let mut out = [0u8; 512];
+        let size = publish(
+            &mut out,
+            &signer,
+            0,
+            payload.as_bytes(),
+            false,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+
+        let entry = decode(&out[..size]).unwrap();
+        let mut unsigned = [0u8; 512];
+        let unsigned_size = entry.encode_for_signing(&mut unsigned).unwrap();
+
+        assert_eq!(entry.author, signer.key_pair.public);
+        assert_eq!(
+            *signer.signed.borrow(),
+            vec![unsigned[..unsigned_size].to_vec()]
+        );
+        assert!(verify(&out[..size], Some(payload.as_bytes()), None, None).is_ok());
+    }
+
+    #[test]
+    fn publish_with_failing_signer_errors() {
+        let mut csprng: OsRng = OsRng {};
+        let key_pair: Keypair = Keypair::generate(&mut csprng);
+        let signer = FailingSigner(key_pair.public);
+
+        let payload = "hello bamboo!";
+        let mut out = [0u8; 512];
+        match publish(
+            &mut out,
+            &signer,
+            0,
+            payload.as_bytes(),
+            false,
+            None,
+            None,
+            None,
+        ) {
+            Err(PublishError::PublishSignFailed) => {}
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn publish_with_links_matches_publish() {
+        let mut csprng: OsRng = OsRng {};
+        let key_pair: Keypair = Keypair::generate(&mut csprng);
+        let payload = "hello bamboo!";
+
+        let mut entries: Vec<Vec<u8>> = Vec::new();
+
+        for seq_num in 1..=5u64 {
+            let previous_seq_num = if seq_num > 1 { Some(seq_num - 1) } else { None };
+            let backlink_bytes = previous_seq_num.map(|n| &entries[n as usize - 1][..]);
+            let lipmaa_bytes = previous_seq_num.map(|_| &entries[lipmaa(seq_num) as usize - 1][..]);
+
+            let mut expected = [0u8; 512];
+            let expected_size = publish(
+                &mut expected,
+                &key_pair,
+                0,
+                payload.as_bytes(),
+                false,
+                previous_seq_num,
+                lipmaa_bytes,
+                backlink_bytes,
+            )
+            .unwrap();
+
+            let backlink = backlink_bytes.map(new_blake2b);
+            let lipmaa_link = lipmaa_bytes.map(new_blake2b);
+
+            let mut out = [0u8; 512];
+            let size = publish_with_links(
+                &mut out,
+                &key_pair,
+                0,
+                payload.as_bytes(),
+                false,
+                previous_seq_num,
+                lipmaa_link.as_ref(),
+                backlink.as_ref(),
+                false,
+            )
+            .unwrap();
+
+            assert_eq!(&out[..size], &expected[..expected_size]);
+            entries.push(out[..size].to_vec());
+        }
+    }
+
+    #[test]
+    fn publish_with_links_after_end_of_feed_errors() {
+        let mut csprng: OsRng = OsRng {};
+        let key_pair: Keypair = Keypair::generate(&mut csprng);
+        let payload = "hello bamboo!";
+
+        let backlink = new_blake2b(b"previous entry");
+
+        let mut out = [0u8; 512];
+        match publish_with_links(
+            &mut out,
+            &key_pair,
+            0,
+            payload.as_bytes(),
+            false,
+            Some(1),
+            None,
+            Some(&backlink),
+            true,
+        ) {
+            Err(PublishError::PublishAfterEndOfFeed) => {}
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn publish_with_links_requires_lipmaa_link_when_it_differs_from_backlink() {
+        let mut csprng: OsRng = OsRng {};
+        let key_pair: Keypair = Keypair::generate(&mut csprng);
+        let payload = "hello bamboo!";
+
+        let backlink = new_blake2b(b"previous entry");
+
+        // Entry 4 links back to entry 1 as its lipmaa link.
+        assert_ne!(lipmaa(4), 3);
+
+        let mut out = [0u8; 512];
+        match publish_with_links(
+            &mut out,
+            &key_pair,
+            0,
+            payload.as_bytes(),
+            false,
+            Some(3),
+            None,
+            Some(&backlink),
+            false,
+        ) {
+            Err(PublishError::PublishWithoutLipmaaEntry) => {}
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn owned_entry_converts_to_and_from_entry() {
+        let bytes = three_published_entries();
+
+        decode_many(&bytes).for_each(|decoded_entry| {
+            let decoded_entry = decoded_entry.unwrap();
+            let owned = OwnedEntry::try_from(&decoded_entry.entry).unwrap();
+
+            assert_eq!(owned.as_entry(), decoded_entry.entry);
+
+            let mut encoded = [0u8; 512];
+            let size = owned.as_entry().encode(&mut encoded).unwrap();
+            assert_eq!(&encoded[..size], decoded_entry.bytes);
+        });
+    }
+
+    #[test]
+    fn owned_entry_serde_round_trip() {
+        let bytes = three_published_entries();
+
+        decode_many(&bytes).for_each(|decoded_entry| {
+            let entry = decoded_entry.unwrap().entry;
+            let owned = OwnedEntry::try_from(&entry).unwrap();
+
+            let string = serde_json::to_string(&owned).unwrap();
+            let parsed: OwnedEntry = serde_json::from_str(&string).unwrap();
+            assert_eq!(parsed, owned);
+
+            // An owned entry serializes the same way as the entry it came from.
+            assert_eq!(string, serde_json::to_string(&entry).unwrap());
+        });
+    }
+
+    #[test]
+    fn owned_entry_can_be_cloned_and_hashed() {
+        let bytes = three_published_entries();
+
+        let owned_entries = decode_many(&bytes)
+            .map(|decoded_entry| OwnedEntry::try_from(decoded_entry.unwrap().entry).unwrap())
+            .collect::<Vec<_>>();
+
+        let mut set = HashSet::new();
+        owned_entries.iter().for_each(|entry| {
+            set.insert(entry.clone());
+        });
+        owned_entries.iter().for_each(|entry| {
+            set.insert(entry.clone());
+        });
+
+        assert_eq!(set.len(), 3);
+        owned_entries
+            .iter()
+            .for_each(|entry| assert!(set.contains(entry)));
+    }
+
+    #[test]
+    fn entry_builder_builds_valid_entries() {
+        let mut csprng: OsRng = OsRng {};
+        let key_pair: Keypair = Keypair::generate(&mut csprng);
+
+        let owned = EntryBuilder::new()
+            .author(key_pair.public)
+            .log_id(3)
+            .payload(b"hello bamboo!")
+            .seq_num(4)
+            .backlink(new_blake2b(b"entry 3"))
+            .lipmaa_link(new_blake2b(b"entry 1"))
+            .build()
+            .unwrap();
+
+        assert_eq!(owned.seq_num, 4);
+        assert_eq!(owned.log_id, 3);
+        assert_eq!(owned.payload_size, 13);
+        assert_eq!(owned.payload_hash, new_blake2b(b"hello bamboo!"));
+        assert_eq!(owned.sig, None);
+
+        let mut encoded = [0u8; 512];
+        let size = owned.as_entry().encode_for_signing(&mut encoded).unwrap();
+        assert_eq!(size, owned.as_entry().encoding_length());
+    }
+
+    #[test]
+    fn entry_builder_checks_fields() {
+        let mut csprng: OsRng = OsRng {};
+        let key_pair: Keypair = Keypair::generate(&mut csprng);
+
+        let builder = || {
+            EntryBuilder::new()
+                .author(key_pair.public)
+                .payload(b"hello bamboo!")
+        };
+
+        match EntryBuilder::new().payload(b"hello bamboo!").build() {
+            Err(OwnedEntryError::BuildWithoutAuthor) => {}
+            other => panic!("{:?}", other),
+        }
+
+        match EntryBuilder::new().author(key_pair.public).build() {
+            Err(OwnedEntryError::BuildWithoutPayloadHash) => {}
+            other => panic!("{:?}", other),
+        }
+
+        match builder().seq_num(0).build() {
+            Err(OwnedEntryError::BuildSeqIsZero) => {}
+            other => panic!("{:?}", other),
+        }
+
+        match builder().backlink(new_blake2b(b"entry 0")).build() {
+            Err(OwnedEntryError::BuildFirstEntryHasLinks) => {}
+            other => panic!("{:?}", other),
+        }
+
+        match builder().seq_num(2).build() {
+            Err(OwnedEntryError::BuildWithoutBacklink { seq_num: 2 }) => {}
+            other => panic!("{:?}", other),
+        }
+
+        match builder()
+            .seq_num(4)
+            .backlink(new_blake2b(b"entry 3"))
+            .build()
+        {
+            Err(OwnedEntryError::BuildWithoutLipmaaLink { seq_num: 4 }) => {}
+            other => panic!("{:?}", other),
+        }
+
+        match builder()
+            .seq_num(2)
+            .backlink(new_blake2b(b"entry 1"))
+            .lipmaa_link(new_blake2b(b"entry 1"))
+            .build()
+        {
+            Err(OwnedEntryError::BuildWithUnneededLipmaaLink { seq_num: 2 }) => {}
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn owned_entry_try_from_rejects_invalid_hash_length() {
+        let mut csprng: OsRng = OsRng {};
+        let key_pair: Keypair = Keypair::generate(&mut csprng);
+
+        let short_hash = [0xAA; 32];
+        let entry = Entry::<&[u8], &[u8]> {
+            log_id: 0,
+            is_end_of_feed: false,
+            payload_hash: YamfHash::Blake2b(&short_hash[..]),
+            payload_size: 1,
+            author: key_pair.public,
+            seq_num: 1,
+            backlink: None,
+            lipmaa_link: None,
+            sig: None,
+        };
+
+        match OwnedEntry::try_from(&entry) {
+            Err(OwnedEntryError::InvalidHashLength {
+                length: 32,
+                expected: 64,
+            }) => {}
+            other => panic!("{:?}", other),
+        }
+    }
+
+    fn publish_entries(
+        key_pair: &Keypair,
+        hash_algorithm: HashAlgorithm,
+        log_id: u64,
+        count: u64,
+    ) -> Vec<Vec<u8>> {
+        let mut entries: Vec<Vec<u8>> = Vec::new();
+
+        for seq_num in 1..=count {
+            let lipmaa_entry = (seq_num > 1).then(|| &entries[lipmaa(seq_num) as usize - 1][..]);
+            let backlink = (seq_num > 1).then(|| &entries[seq_num as usize - 2][..]);
+            let payload = format!("message number {}", seq_num);
+
+            let mut out = [0u8; 512];
+            let size = publish_with_hash_algorithm(
+                &mut out,
+                key_pair,
+                hash_algorithm,
+                log_id,
+                payload.as_bytes(),
+                false,
+                Some(seq_num - 1).filter(|seq_num| *seq_num > 0),
+                lipmaa_entry,
+                backlink,
+            )
+            .unwrap();
+
+            entries.push(out[..size].to_vec());
+        }
+
+        entries
+    }
+
+    #[test]
+    fn publish_and_verify_blake3_entries() {
+        let mut csprng: OsRng = OsRng {};
+        let key_pair: Keypair = Keypair::generate(&mut csprng);
+
+        let entries = publish_entries(&key_pair, HashAlgorithm::Blake3, 0, 5);
+
+        entries.iter().enumerate().for_each(|(index, bytes)| {
+            let seq_num = index as u64 + 1;
+            let entry = decode(bytes).unwrap();
+            let payload = format!("message number {}", seq_num);
+
+            assert_eq!(entry.payload_hash, new_blake3(payload.as_bytes()));
+            assert_eq!(entry.payload_hash.algorithm(), HashAlgorithm::Blake3);
+            assert_eq!(entry.payload_hash.as_bytes().len(), BLAKE3_HASH_SIZE);
+
+            let lipmaa_entry = (seq_num > 1).then(|| &entries[lipmaa(seq_num) as usize - 1][..]);
+            let backlink = (seq_num > 1).then(|| &entries[index.saturating_sub(1)][..]);
+
+            if let Some(backlink) = backlink {
+                assert_eq!(entry.backlink.unwrap(), new_blake3(backlink));
+            }
+
+            verify(bytes, Some(payload.as_bytes()), lipmaa_entry, backlink).unwrap();
+            decode_strict(bytes).unwrap();
+        });
+
+        let entries_and_payloads = entries
+            .iter()
+            .enumerate()
+            .map(|(index, bytes)| (bytes, Some(format!("message number {}", index + 1))))
+            .collect::<Vec<_>>();
+        verify_batch(&entries_and_payloads).unwrap();
+    }
+
+    #[test]
+    fn verify_blake3_entry_checks_payload_hash() {
+        let mut csprng: OsRng = OsRng {};
+        let key_pair: Keypair = Keypair::generate(&mut csprng);
+
+        let entries = publish_entries(&key_pair, HashAlgorithm::Blake3, 0, 1);
+        let entry = decode(&entries[0]).unwrap();
+
+        // The same bytes hashed with blake2b are not equal to the blake3 hash.
+        assert_ne!(entry.payload_hash, new_blake2b(b"message number 1"));
+        verify(&entries[0], Some(b"message number 1"), None, None).unwrap();
+
+        match verify(&entries[0], Some(b"message number 2"), None, None) {
+            Err(VerifyError::PayloadHashDidNotMatch {}) => {}
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn publish_rejects_mixing_hash_algorithms_in_a_log() {
+        let mut csprng: OsRng = OsRng {};
+        let key_pair: Keypair = Keypair::generate(&mut csprng);
+
+        let entries = publish_entries(&key_pair, HashAlgorithm::Blake3, 0, 1);
+
+        let mut out = [0u8; 512];
+        match publish(
+            &mut out,
+            &key_pair,
+            0,
+            b"blake2b",
+            false,
+            Some(1),
+            Some(&entries[0]),
+            Some(&entries[0]),
+        ) {
+            Err(PublishError::PublishWithDifferentHashAlgorithm {
+                expected: HashAlgorithm::Blake2b,
+                actual: HashAlgorithm::Blake3,
+            }) => {}
+            other => panic!("{:?}", other),
+        }
+
+        // The lipmaa entry is checked as well as the backlink.
+        let blake2b_entries = publish_entries(&key_pair, HashAlgorithm::Blake2b, 0, 3);
+        match publish(
+            &mut out,
+            &key_pair,
+            0,
+            b"blake2b",
+            false,
+            Some(3),
+            Some(&entries[0]),
+            Some(&blake2b_entries[2]),
+        ) {
+            Err(PublishError::PublishWithDifferentHashAlgorithm {
+                expected: HashAlgorithm::Blake2b,
+                actual: HashAlgorithm::Blake3,
+            }) => {}
+            other => panic!("{:?}", other),
+        }
+
+        let backlink = new_blake3(&entries[0]);
+        match publish_with_links(
+            &mut out,
+            &key_pair,
+            0,
+            b"blake2b",
+            false,
+            Some(1),
+            None,
+            Some(&backlink),
+            false,
+        ) {
+            Err(PublishError::PublishWithDifferentHashAlgorithm {
+                expected: HashAlgorithm::Blake2b,
+                actual: HashAlgorithm::Blake3,
+            }) => {}
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn blake3_yamf_hash_encode_decode() {
+        let hash = new_blake3(b"hello bamboo!");
+        assert_eq!(hash.algorithm(), HashAlgorithm::Blake3);
+
+        let mut encoded = Vec::new();
+        hash.encode_write(&mut encoded).unwrap();
+        assert_eq!(encoded.len(), hash.encoding_length());
+        assert_eq!(encoded[0], 1);
+        assert_eq!(encoded[1], BLAKE3_HASH_SIZE as u8);
+
+        encoded.push(0xAA);
+        let (decoded, remaining) = YamfHash::<&[u8]>::decode(&encoded).unwrap();
+        assert_eq!(decoded, hash);
+        assert_eq!(remaining, &[0xAA]);
+
+        let string = serde_json::to_string(&hash).unwrap();
+        let parsed: YamfHash<Vec<u8>> = serde_json::from_str(&string).unwrap();
+        assert_eq!(parsed, hash);
+
+        assert_eq!(HashAlgorithm::Blake3.hash(b"hello bamboo!"), hash);
+        assert_ne!(new_blake2b(b"hello bamboo!"), hash);
+    }
+
+    #[test]
+    fn verify_entry_returns_the_entry_and_its_hash() {
+        let mut csprng: OsRng = OsRng {};
+        let key_pair: Keypair = Keypair::generate(&mut csprng);
+
+        let entries = publish_entries(&key_pair, HashAlgorithm::Blake2b, 0, 4);
+
+        let verified = verify_entry(&entries[0], Some(b"message number 1"), None, None).unwrap();
+        assert_eq!(verified.entry(), &decode(&entries[0]).unwrap());
+        assert_eq!(verified.bytes(), &entries[0][..]);
+        assert_eq!(verified.hash(), &new_blake2b(&entries[0]));
+        assert_eq!(
+            verified.checks(),
+            VerifiedChecks {
+                signature: true,
+                payload: true,
+                backlink: false,
+                lipmaa_link: false,
+            }
+        );
+
+        // The next entry links to the hash of the verified one.
+        let next = verify_entry(&entries[1], None, Some(&entries[0]), Some(&entries[0])).unwrap();
+        assert_eq!(next.entry().backlink.as_ref().unwrap(), verified.hash());
+        assert_eq!(
+            next.checks(),
+            VerifiedChecks {
+                signature: true,
+                payload: false,
+                backlink: true,
+                lipmaa_link: true,
+            }
+        );
+
+        // Partially replicated, without the backlink.
+        let fourth = verify_entry(&entries[3], None, Some(&entries[0]), None).unwrap();
+        assert_eq!(
+            fourth.checks(),
+            VerifiedChecks {
+                signature: true,
+                payload: false,
+                backlink: false,
+                lipmaa_link: true,
+            }
+        );
+        assert_eq!(fourth.into_entry().seq_num, 4);
+    }
+
+    #[test]
+    fn verify_entry_hashes_with_the_entry_hash_algorithm() {
+        let mut csprng: OsRng = OsRng {};
+        let key_pair: Keypair = Keypair::generate(&mut csprng);
+
+        let entries = publish_entries(&key_pair, HashAlgorithm::Blake3, 0, 2);
+
+        let verified = verify_entry(&entries[0], None, None, None).unwrap();
+        assert_eq!(verified.hash(), &new_blake3(&entries[0]));
+
+        let next = verify_entry(&entries[1], None, None, Some(&entries[0])).unwrap();
+        assert_eq!(next.entry().backlink.as_ref().unwrap(), verified.hash());
+    }
+
+    #[test]
+    fn verify_entry_leaves_trailing_bytes_out_of_the_hash() {
+        let mut csprng: OsRng = OsRng {};
+        let key_pair: Keypair = Keypair::generate(&mut csprng);
+
+        let entries = publish_entries(&key_pair, HashAlgorithm::Blake2b, 0, 2);
+
+        let mut with_garbage = entries[0].clone();
+        with_garbage.extend_from_slice(b"garbage");
+
+        let verified = verify_entry(&with_garbage, None, None, None).unwrap();
+        assert_eq!(verified.bytes(), &entries[0][..]);
+        assert_eq!(verified.hash(), &new_blake2b(&entries[0]));
+
+        let next = verify_entry(&entries[1], None, None, Some(&entries[0])).unwrap();
+        assert_eq!(next.entry().backlink.as_ref().unwrap(), verified.hash());
+    }
+
+    #[test]
+    fn verify_entry_fails_like_verify() {
+        let mut csprng: OsRng = OsRng {};
+        let key_pair: Keypair = Keypair::generate(&mut csprng);
+
+        let entries = publish_entries(&key_pair, HashAlgorithm::Blake2b, 0, 1);
+
+        match verify_entry(&entries[0], Some(b"not the payload"), None, None) {
+            Err(VerifyError::PayloadHashDidNotMatch {}) => {}
+            other => panic!("{:?}", other),
+        }
+    }
+
+    fn interleaved_feeds() -> Vec<(Vec<u8>, Option<String>)> {
+        let mut csprng: OsRng = OsRng {};
+        let alice: Keypair = Keypair::generate(&mut csprng);
+        let bob: Keypair = Keypair::generate(&mut csprng);
+
+        let feeds = [
+            publish_entries(&alice, HashAlgorithm::Blake2b, 0, 5),
+            publish_entries(&alice, HashAlgorithm::Blake2b, 1, 5),
+            publish_entries(&bob, HashAlgorithm::Blake3, 0, 5),
+        ];
+
+        // Round robin the feeds so no two entries from the same feed are next to each other.
+        (0..5)
+            .flat_map(|index| feeds.iter().map(move |feed| feed[index].clone()))
+            .enumerate()
+            .map(|(index, bytes)| (bytes, Some(format!("message number {}", index / 3 + 1))))
+            .collect()
+    }
+
+    #[test]
+    fn verify_batch_multi_feed_verifies_interleaved_feeds() {
+        let entries_and_payloads = interleaved_feeds();
+
+        verify_batch_multi_feed(&entries_and_payloads).unwrap();
+    }
+
+    #[test]
+    fn verify_batch_multi_feed_checks_each_feed() {
+        let mut entries_and_payloads = interleaved_feeds();
+        entries_and_payloads[7].1 = Some("not the payload".to_string());
+
+        match verify_batch_multi_feed(&entries_and_payloads) {
+            Err(VerifyError::PayloadHashDidNotMatch {}) => {}
+            other => panic!("{:?}", other),
+        }
+
+        let mut entries_and_payloads = interleaved_feeds();
+        // Corrupt the signature of bob's second entry, so bob's third entry doesn't link to it.
+        let mut bytes = entries_and_payloads[5].0.clone();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0xFF;
+        entries_and_payloads[5].0 = bytes;
+
+        match verify_batch_multi_feed(&entries_and_payloads) {
+            Err(VerifyError::BacklinkHashDoesNotMatch {}) => {}
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn verify_batch_multi_feed_rejects_duplicate_seq_nums() {
+        // An entry with the wrong payload, then the same entry with the right one.
+        let mut entries_and_payloads = interleaved_feeds();
+        let (bytes, _) = entries_and_payloads[7].clone();
+        entries_and_payloads.insert(7, (bytes, Some("not the payload".to_string())));
+
+        match verify_batch_multi_feed(&entries_and_payloads) {
+            Err(VerifyError::DuplicateSeqNum { seq_num: 3 }) => {}
+            other => panic!("{:?}", other),
+        }
+
+        let policy = VerifyPolicy::new();
+        match verify_batch_multi_feed_with_policy(&entries_and_payloads, &policy) {
+            Err(VerifyError::DuplicateSeqNum { seq_num: 3 }) => {}
+            other => panic!("{:?}", other),
+        }
+
+        let mut csprng: OsRng = OsRng {};
+        let key_pair: Keypair = Keypair::generate(&mut csprng);
+        let mut entries_and_payloads = publish_entries(&key_pair, HashAlgorithm::Blake2b, 0, 3)
+            .into_iter()
+            .enumerate()
+            .map(|(index, bytes)| (bytes, Some(format!("message number {}", index + 1))))
+            .collect::<Vec<_>>();
+        let (bytes, _) = entries_and_payloads[1].clone();
+        entries_and_payloads.insert(1, (bytes, Some("not the payload".to_string())));
+
+        match verify_batch(&entries_and_payloads) {
+            Err(VerifyError::DuplicateSeqNum { seq_num: 2 }) => {}
+            other => panic!("{:?}", other),
+        }
+    }
+
+    fn corrupt_signature(bytes: &mut [u8]) {
+        let signature_start = bytes.len() - ED25519_SIGNATURE_SIZE;
+        bytes[signature_start] ^= 0xFF;
+    }
+
+    #[test]
+    fn verify_batch_report_returns_a_result_per_entry() {
+        let entries_and_payloads = interleaved_feeds();
+
+        let results = verify_batch_report(&entries_and_payloads);
+        assert_eq!(results.len(), entries_and_payloads.len());
+        assert!(results.iter().all(|result| result.is_ok()));
+    }
+
+    #[test]
+    fn verify_batch_report_only_rejects_bad_entries() {
+        let mut entries_and_payloads = interleaved_feeds();
+        // The last entry of each feed, nothing else in the batch links to them.
+        entries_and_payloads[7].1 = Some("not the payload".to_string());
+        corrupt_signature(&mut entries_and_payloads[12].0);
+        corrupt_signature(&mut entries_and_payloads[13].0);
+        entries_and_payloads[14].0 = vec![0xFF; 8];
+
+        let results = verify_batch_report(&entries_and_payloads);
+        assert_eq!(results.len(), entries_and_payloads.len());
+
+        results
+            .into_iter()
+            .enumerate()
+            .for_each(|(index, result)| match (index, result) {
+                (7, Err(VerifyError::PayloadHashDidNotMatch {})) => {}
+                (12, Err(VerifyError::InvalidSignature)) => {}
+                (13, Err(VerifyError::InvalidSignature)) => {}
+                (14, Err(VerifyError::DecodeEntry { .. })) => {}
+                (index, Ok(())) if ![7, 12, 13, 14].contains(&index) => {}
+                (index, other) => panic!("{}: {:?}", index, other),
+            });
+    }
+
+    #[test]
+    fn verify_batch_report_rejects_duplicate_seq_nums() {
+        // An entry with the wrong payload, then the same entry with the right one.
+        let mut entries_and_payloads = interleaved_feeds();
+        let (bytes, _) = entries_and_payloads[7].clone();
+        entries_and_payloads.insert(7, (bytes, Some("not the payload".to_string())));
+
+        let results = verify_batch_report(&entries_and_payloads);
+        assert_eq!(results.len(), entries_and_payloads.len());
+
+        results
+            .into_iter()
+            .enumerate()
+            .for_each(|(index, result)| match (index, result) {
+                (7, Err(VerifyError::DuplicateSeqNum { seq_num: 3 })) => {}
+                (8, Err(VerifyError::DuplicateSeqNum { seq_num: 3 })) => {}
+                (index, Ok(())) if ![7, 8].contains(&index) => {}
+                (index, other) => panic!("{}: {:?}", index, other),
+            });
+    }
+
+    #[test]
+    fn verify_batch_signatures_report_bisects_to_bad_signatures() {
+        let mut csprng: OsRng = OsRng {};
+        let key_pair: Keypair = Keypair::generate(&mut csprng);
+
+        let mut entries = publish_entries(&key_pair, HashAlgorithm::Blake2b, 0, 300);
+        corrupt_signature(&mut entries[10]);
+        corrupt_signature(&mut entries[11]);
+        corrupt_signature(&mut entries[200]);
+
+        assert!(verify_batch_signatures(&entries).is_err());
+
+        let results = verify_batch_signatures_report(&entries);
+        assert_eq!(results.len(), entries.len());
+
+        results
+            .into_iter()
+            .enumerate()
+            .for_each(|(index, result)| match (index, result) {
+                (10, Err(VerifyError::InvalidSignature)) => {}
+                (11, Err(VerifyError::InvalidSignature)) => {}
+                (200, Err(VerifyError::InvalidSignature)) => {}
+                (index, Ok(())) if ![10, 11, 200].contains(&index) => {}
+                (index, other) => panic!("{}: {:?}", index, other),
+            });
+    }
+
+    #[test]
+    fn verify_batch_sequential_verifies_interleaved_feeds() {
+        let entries_and_payloads = interleaved_feeds();
+
+        verify_batch_sequential(&entries_and_payloads, &mut OsRng {}).unwrap();
+
+        let mut bad_payload = interleaved_feeds();
+        bad_payload[4].1 = Some("not the payload".to_string());
+        match verify_batch_sequential(&bad_payload, &mut OsRng {}) {
+            Err(VerifyError::PayloadHashDidNotMatch {}) => {}
+            other => panic!("{:?}", other),
+        }
+
+        let mut bad_signature = interleaved_feeds();
+        corrupt_signature(&mut bad_signature[14].0);
+        match verify_batch_sequential(&bad_signature, &mut OsRng {}) {
+            Err(VerifyError::InvalidSignature) => {}
+            other => panic!("{:?}", other),
+        }
+
+        // An entry with the wrong payload, then the same entry with the right one.
+        let mut duplicate = interleaved_feeds();
+        let (bytes, _) = duplicate[7].clone();
+        duplicate.insert(7, (bytes, Some("not the payload".to_string())));
+        match verify_batch_sequential(&duplicate, &mut OsRng {}) {
+            Err(VerifyError::DuplicateSeqNum { seq_num: 3 }) => {}
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn yamf_hasher_matches_hashing_all_at_once() {
+        let bytes: Vec<u8> = (0..300_000u32).map(|i| i as u8).collect();
+
+        for &algorithm in &[HashAlgorithm::Blake2b, HashAlgorithm::Blake3] {
+            let mut hasher = YamfHasher::new(algorithm);
+            bytes
+                .chunks(1000)
+                .chain(Some(&bytes[..200_000]))
+                .for_each(|chunk| {
+                    hasher.update(chunk);
+                });
+
+            let mut expected = bytes.clone();
+            expected.extend_from_slice(&bytes[..200_000]);
+            assert_eq!(hasher.algorithm(), algorithm);
+            assert_eq!(hasher.bytes_hashed(), expected.len() as u64);
+            assert_eq!(hasher.finalize(), algorithm.hash(&expected));
+        }
+    }
+
+    #[test]
+    fn publish_and_verify_from_reader() {
+        let mut csprng: OsRng = OsRng {};
+        let key_pair: Keypair = Keypair::generate(&mut csprng);
+        let payload: Vec<u8> = (0..1_000_000u32).map(|i| (i % 251) as u8).collect();
+
+        for &algorithm in &[HashAlgorithm::Blake2b, HashAlgorithm::Blake3] {
+            let previous = publish_entries(&key_pair, algorithm, 0, 1);
+
+            let mut out = [0u8; 512];
+            let size = publish_from_reader(
+                &mut out,
+                &key_pair,
+                algorithm,
+                0,
+                Cursor::new(&payload),
+                false,
+                Some(1),
+                Some(&previous[0]),
+                Some(&previous[0]),
+            )
+            .unwrap();
+
+            let mut expected = [0u8; 512];
+            let expected_size = publish_with_hash_algorithm(
+                &mut expected,
+                &key_pair,
+                algorithm,
+                0,
+                &payload,
+                false,
+                Some(1),
+                Some(&previous[0]),
+                Some(&previous[0]),
+            )
+            .unwrap();
+            assert_eq!(&out[..size], &expected[..expected_size]);
+
+            let entry = decode(&out[..size]).unwrap();
+            assert_eq!(entry.payload_size, payload.len() as u64);
+
+            verify_from_reader(
+                &out[..size],
+                Some(Cursor::new(&payload)),
+                None,
+                Some(&previous[0]),
+            )
+            .unwrap();
+            verify_from_reader::<Cursor<&[u8]>>(&out[..size], None, None, None).unwrap();
+
+            match verify_from_reader(&out[..size], Some(Cursor::new(&payload[1..])), None, None) {
+                Err(VerifyError::PayloadHashDidNotMatch {}) => {}
+                other => panic!("{:?}", other),
+            }
+        }
+    }
+
+    struct FailingReader;
+
+    impl Read for FailingReader {
+        fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+            Err(io::Error::other("disk on fire"))
+        }
+    }
+
+    #[test]
+    fn publish_and_verify_from_reader_report_read_errors() {
+        let mut csprng: OsRng = OsRng {};
+        let key_pair: Keypair = Keypair::generate(&mut csprng);
+
+        let mut out = [0u8; 512];
+        match publish_from_reader(
+            &mut out,
+            &key_pair,
+            HashAlgorithm::Blake2b,
+            0,
+            FailingReader,
+            false,
+            None,
+            None,
+            None,
+        ) {
+            Err(PublishError::PublishReadPayload { source }) => {
+                assert_eq!(source.to_string(), "disk on fire")
+            }
+            other => panic!("{:?}", other),
+        }
+
+        let entries = publish_entries(&key_pair, HashAlgorithm::Blake2b, 0, 1);
+        match verify_from_reader(&entries[0], Some(FailingReader), None, None) {
+            Err(VerifyError::ReadPayload { source }) => {
+                assert_eq!(source.to_string(), "disk on fire")
+            }
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn publish_and_verify_with_payload_hash() {
+        let mut csprng: OsRng = OsRng {};
+        let key_pair: Keypair = Keypair::generate(&mut csprng);
+        let payload = b"stored somewhere else";
+
+        for &algorithm in &[HashAlgorithm::Blake2b, HashAlgorithm::Blake3] {
+            let previous = publish_entries(&key_pair, algorithm, 0, 1);
+            let payload_hash = algorithm.hash(payload);
+
+            let mut out = [0u8; 512];
+            let size = publish_with_payload_hash(
+                &mut out,
+                &key_pair,
+                0,
+                &payload_hash,
+                payload.len() as u64,
+                false,
+                Some(1),
+                Some(&previous[0]),
+                Some(&previous[0]),
+            )
+            .unwrap();
+
+            let mut expected = [0u8; 512];
+            let expected_size = publish_with_hash_algorithm(
+                &mut expected,
+                &key_pair,
+                algorithm,
+                0,
+                payload,
+                false,
+                Some(1),
+                Some(&previous[0]),
+                Some(&previous[0]),
+            )
+            .unwrap();
+            assert_eq!(&out[..size], &expected[..expected_size]);
+
+            let entry_bytes = &out[..size];
+            verify_with_payload_hash(
+                entry_bytes,
+                &payload_hash,
+                payload.len() as u64,
+                None,
+                Some(&previous[0]),
+            )
+            .unwrap();
+
+            match verify_with_payload_hash(
+                entry_bytes,
+                &algorithm.hash(b"something else"),
+                payload.len() as u64,
+                None,
+                None,
+            ) {
+                Err(VerifyError::PayloadHashDidNotMatch {}) => {}
+                other => panic!("{:?}", other),
+            }
+
+            match verify_with_payload_hash(entry_bytes, &payload_hash, 1, None, None) {
+                Err(VerifyError::PayloadLengthDidNotMatch { actual: 1, .. }) => {}
+                other => panic!("{:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn publish_with_payload_hash_checks_hash_length() {
+        let mut csprng: OsRng = OsRng {};
+        let key_pair: Keypair = Keypair::generate(&mut csprng);
+        let short_hash = [0xAB; BLAKE3_HASH_SIZE];
+
+        let mut out = [0u8; 512];
+        match publish_with_payload_hash(
+            &mut out,
+            &key_pair,
+            0,
+            &YamfHash::Blake2b(&short_hash[..]),
+            10,
+            false,
+            None,
+            None,
+            None,
+        ) {
+            Err(PublishError::PublishWithInvalidPayloadHashLength {
+                length: BLAKE3_HASH_SIZE,
+                expected: BLAKE2B_HASH_SIZE,
+            }) => {}
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn inclusion_proofs_verify() {
+        let mut csprng: OsRng = OsRng {};
+        let key_pair: Keypair = Keypair::generate(&mut csprng);
+
+        for &algorithm in &[HashAlgorithm::Blake2b, HashAlgorithm::Blake3] {
+            let entries = publish_entries(&key_pair, algorithm, 0, 40);
+            let get_entry = |seq_num: u64| entries.get(seq_num as usize - 1).map(Vec::as_slice);
+
+            for &(head_seq_num, seq_num) in &[(40, 1), (40, 13), (40, 39), (27, 6), (5, 5), (1, 1)]
+            {
+                let proof = inclusion_proof(head_seq_num, seq_num, get_entry).unwrap();
+                let verified = verify_inclusion_proof(&proof, seq_num).unwrap();
+
+                assert_eq!(verified.head().seq_num, head_seq_num);
+                assert_eq!(verified.head().author, key_pair.public);
+                assert_eq!(verified.entry().seq_num, seq_num);
+                assert_eq!(verified.entry_bytes(), &entries[seq_num as usize - 1][..]);
+            }
+        }
+    }
+
+    #[test]
+    fn inclusion_proofs_reject_bad_proofs() {
+        let mut csprng: OsRng = OsRng {};
+        let key_pair: Keypair = Keypair::generate(&mut csprng);
+        let entries = publish_entries(&key_pair, HashAlgorithm::Blake2b, 0, 20);
+        let other_log = publish_entries(&key_pair, HashAlgorithm::Blake2b, 1, 20);
+        let get_entry = |seq_num: u64| entries.get(seq_num as usize - 1).map(Vec::as_slice);
+
+        // 20 -> 19 -> 18 -> 17 -> 13 -> 12 -> 8 -> 7 -> 6
+        let proof_from = |entries: &[&[u8]]| entries.concat();
+        let proof_entries: Vec<&[u8]> = lipmaa_path(20, 6)
+            .unwrap()
+            .map(|seq_num| &entries[seq_num as usize - 1][..])
+            .collect();
+        assert!(inclusion_proof(20, 6, get_entry).unwrap() == proof_from(&proof_entries));
+
+        match verify_inclusion_proof(&[], 6) {
+            Err(InclusionProofError::EmptyProof) => {}
+            other => panic!("{:?}", other),
+        }
+
+        let mut bad_head = proof_entries[0].to_vec();
+        corrupt_signature(&mut bad_head);
+        let mut bad_head_entries = proof_entries.clone();
+        bad_head_entries[0] = &bad_head;
+        match verify_inclusion_proof(&proof_from(&bad_head_entries), 6) {
+            Err(InclusionProofError::VerifyProofHead {
+                source: VerifyError::InvalidSignature,
+            }) => {}
+            other => panic!("{:?}", other),
+        }
+
+        let mut swapped = proof_entries.clone();
+        swapped[2] = &other_log[17];
+        match verify_inclusion_proof(&proof_from(&swapped), 6) {
+            Err(InclusionProofError::VerifyProofLink {
+                seq_num: 19,
+                source: VerifyError::BacklinkLogIdDoesNotMatch { .. },
+            }) => {}
+            other => panic!("{:?}", other),
+        }
+
+        let mut skipped = proof_entries.clone();
+        skipped.remove(6);
+        match verify_inclusion_proof(&proof_from(&skipped), 6) {
+            Err(InclusionProofError::ProofEntriesNotLinked {
+                seq_num: 12,
+                next_seq_num: 7,
+            }) => {}
+            other => panic!("{:?}", other),
+        }
+
+        match verify_inclusion_proof(&proof_from(&proof_entries), 7) {
+            Err(InclusionProofError::ProofEndsAtWrongEntry {
+                actual: 6,
+                expected: 7,
+            }) => {}
+            other => panic!("{:?}", other),
+        }
+
+        match inclusion_proof(20, 6, |seq_num| get_entry(seq_num).filter(|_| seq_num != 8)) {
+            Err(InclusionProofError::ProofEntryNotFound { seq_num: 8 }) => {}
+            other => panic!("{:?}", other),
+        }
+
+        match inclusion_proof(6, 20, get_entry) {
+            Err(InclusionProofError::InvalidProofSeqNums { .. }) => {}
+            other => panic!("{:?}", other),
+        }
+    }
+
+    // An entry by the identity point, a weak key that every signature `(s * B, s)` is valid for.
+    fn weak_key_entry() -> Vec<u8> {
+        let mut identity = [0u8; PUBLIC_KEY_LENGTH];
+        identity[0] = 1;
+        let author = PublicKey::from_bytes(&identity).unwrap();
+
+        let s = Scalar::from(42u64);
+        let r = (&s * &ED25519_BASEPOINT_TABLE).compress();
+        let mut sig = [0u8; ED25519_SIGNATURE_SIZE];
+        sig[..32].copy_from_slice(r.as_bytes());
+        sig[32..].copy_from_slice(s.as_bytes());
+
+        let entry = Entry {
+            log_id: 0,
+            is_end_of_feed: false,
+            payload_hash: new_blake2b(b"forged"),
+            payload_size: 6,
+            author,
+            seq_num: 1,
+            backlink: None,
+            lipmaa_link: None,
+            sig: Some(Signature(&sig[..])),
+        };
+
+        let mut out = [0u8; 512];
+        let size = entry.encode(&mut out).unwrap();
+        out[..size].to_vec()
+    }
+
+    #[test]
+    fn is_weak_key_rejects_small_and_mixed_order_keys() {
+        let mut csprng: OsRng = OsRng {};
+        let key_pair: Keypair = Keypair::generate(&mut csprng);
+        assert!(!is_weak_key(&key_pair.public));
+
+        for torsion in EIGHT_TORSION.iter() {
+            let small_order = PublicKey::from_bytes(torsion.compress().as_bytes()).unwrap();
+            assert!(is_weak_key(&small_order));
+
+            let honest_point = CompressedEdwardsY(key_pair.public.to_bytes())
+                .decompress()
+                .unwrap();
+            let mixed_order = (honest_point + torsion).compress();
+            let mixed_order = PublicKey::from_bytes(mixed_order.as_bytes()).unwrap();
+            assert_eq!(is_weak_key(&mixed_order), !torsion.is_identity());
+        }
+    }
+
+    #[test]
+    fn strict_verification_rejects_weak_author_keys() {
+        let forged = weak_key_entry();
+
+        // The lenient paths accept a signature that anyone could have made.
+        verify(&forged, Some(b"forged"), None, None).unwrap();
+        verify_batch_signatures(&[&forged]).unwrap();
+
+        match verify_strict(&forged, Some(b"forged"), None, None) {
+            Err(VerifyError::WeakAuthorKey) => {}
+            other => panic!("{:?}", other),
+        }
+        match verify_batch_signatures_strict(&[&forged]) {
+            Err(VerifyError::WeakAuthorKey) => {}
+            other => panic!("{:?}", other),
+        }
+        let forged_and_payload = [(&forged, Some(b"forged"))];
+        verify_batch_report(&forged_and_payload)[0]
+            .as_ref()
+            .unwrap();
+        match &verify_batch_report_strict(&forged_and_payload)[0] {
+            Err(VerifyError::WeakAuthorKey) => {}
+            other => panic!("{:?}", other),
+        }
+        verify_batch_sequential(&forged_and_payload, &mut OsRng {}).unwrap();
+        match verify_batch_sequential_strict(&forged_and_payload, &mut OsRng {}) {
+            Err(VerifyError::WeakAuthorKey) => {}
+            other => panic!("{:?}", other),
+        }
+        match decode_strict(&forged) {
+            Err(DecodeError::DecodeWeakAuthorKey) => {}
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn strict_verification_rejects_small_order_signature_points() {
+        let mut csprng: OsRng = OsRng {};
+        let key_pair: Keypair = Keypair::generate(&mut csprng);
+        let mut entries = publish_entries(&key_pair, HashAlgorithm::Blake2b, 0, 1);
+        let sig_start = entries[0].len() - ED25519_SIGNATURE_SIZE;
+        entries[0][sig_start..sig_start + 32]
+            .copy_from_slice(EIGHT_TORSION[1].compress().as_bytes());
+
+        match verify(&entries[0], None, None, None) {
+            Err(VerifyError::InvalidSignature) => {}
+            other => panic!("{:?}", other),
+        }
+        match verify_strict(&entries[0], None, None, None) {
+            Err(VerifyError::WeakSignature) => {}
+            other => panic!("{:?}", other),
+        }
+        match verify_batch_signatures_strict(&entries) {
+            Err(VerifyError::WeakSignature) => {}
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn strict_verification_accepts_honest_entries() {
+        let mut csprng: OsRng = OsRng {};
+        let key_pair: Keypair = Keypair::generate(&mut csprng);
+        let entries = publish_entries(&key_pair, HashAlgorithm::Blake2b, 0, 20);
+
+        for (index, entry) in entries.iter().enumerate() {
+            let backlink = index.checked_sub(1).map(|index| &entries[index][..]);
+            let seq_num = index as u64 + 1;
+            let lipmaa_entry = Some(lipmaa(seq_num))
+                .filter(|lipmaa| *lipmaa > 0)
+                .map(|lipmaa| &entries[lipmaa as usize - 1][..]);
+            let payload = format!("message number {}", seq_num);
+
+            verify_strict(entry, Some(payload.as_bytes()), lipmaa_entry, backlink).unwrap();
+            decode_strict(entry).unwrap();
+        }
+
+        let entries_and_payloads: Vec<_> = entries
+            .iter()
+            .enumerate()
+            .map(|(index, entry)| (entry, Some(format!("message number {}", index + 1))))
+            .collect();
+        verify_batch_strict(&entries_and_payloads).unwrap();
+        verify_batch_sequential_strict(&entries_and_payloads, &mut OsRng {}).unwrap();
+        assert!(verify_batch_report_strict(&entries_and_payloads)
+            .iter()
+            .all(|result| result.is_ok()));
+    }
+
+    #[test]
+    fn verifying_the_signature_of_an_unsigned_entry_errors() {
+        let mut csprng: OsRng = OsRng {};
+        let key_pair: Keypair = Keypair::generate(&mut csprng);
+        let entries = publish_entries(&key_pair, HashAlgorithm::Blake2b, 0, 1);
+        let mut entry = decode(&entries[0]).unwrap();
+        entry.sig = None;
+
+        match entry.verify_signature() {
+            Err(VerifyError::InvalidSignature) => {}
+            other => panic!("{:?}", other),
+        }
+        match entry.verify_signature_strict() {
+            Err(VerifyError::InvalidSignature) => {}
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn publish_many_matches_publishing_one_at_a_time() {
+        let mut csprng: OsRng = OsRng {};
+        let key_pair: Keypair = Keypair::generate(&mut csprng);
+
+        for &algorithm in &[HashAlgorithm::Blake2b, HashAlgorithm::Blake3] {
+            let expected = publish_entries(&key_pair, algorithm, 3, 100);
+            let payloads = (1..=100).map(|seq_num| format!("message number {}", seq_num));
+
+            let entries = publish_many(&key_pair, algorithm, 3, None, None::<&[u8]>, payloads)
+                .unwrap()
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+
+            assert_eq!(entries, expected);
+        }
+    }
+
+    #[test]
+    fn publish_many_continues_a_log() {
+        let mut csprng: OsRng = OsRng {};
+        let key_pair: Keypair = Keypair::generate(&mut csprng);
+        let expected = publish_entries(&key_pair, HashAlgorithm::Blake2b, 0, 60);
+        let payloads = (41..=60).map(|seq_num| format!("message number {}", seq_num));
+
+        let ancestors = lipmaa_path(40, 1)
+            .unwrap()
+            .map(|seq_num| &expected[seq_num as usize - 1]);
+        let mut publisher = publish_many(
+            &key_pair,
+            HashAlgorithm::Blake2b,
+            0,
+            Some(40),
+            ancestors,
+            payloads.clone(),
+        )
+        .unwrap();
+        assert_eq!(publisher.previous_seq_num(), Some(40));
+
+        let entries = publisher.by_ref().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(entries, &expected[40..]);
+        assert_eq!(publisher.previous_seq_num(), Some(60));
+
+        // Entries that aren't needed are ignored.
+        let entries = publish_many(
+            &key_pair,
+            HashAlgorithm::Blake2b,
+            0,
+            Some(40),
+            &expected[..40],
+            payloads,
+        )
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+        assert_eq!(entries, &expected[40..]);
+    }
+
+    #[test]
+    fn publish_many_checks_its_starting_state() {
+        let mut csprng: OsRng = OsRng {};
+        let key_pair: Keypair = Keypair::generate(&mut csprng);
+        let entries = publish_entries(&key_pair, HashAlgorithm::Blake2b, 0, 4);
+        let payloads = ["a", "b", "c"];
+        let publish_after = |previous_seq_num, ancestors: &[Vec<u8>]| {
+            publish_many(
+                &key_pair,
+                HashAlgorithm::Blake2b,
+                0,
+                Some(previous_seq_num),
+                ancestors,
+                &payloads,
+            )
+            .map(|_| ())
+        };
+
+        match publish_after(3, &entries[..1]) {
+            Err(PublishError::PublishWithoutBacklinkEntry) => {}
+            other => panic!("{:?}", other),
+        }
+
+        let other_log = publish_entries(&key_pair, HashAlgorithm::Blake2b, 1, 1);
+        match publish_after(1, &other_log) {
+            Err(PublishError::PublishWithIncorrectBacklinkLogId) => {}
+            other => panic!("{:?}", other),
+        }
+
+        let mut out = [0u8; 512];
+        let size = publish(&mut out, &key_pair, 0, b"the end", true, None, None, None).unwrap();
+        match publish_after(1, &[out[..size].to_vec()]) {
+            Err(PublishError::PublishAfterEndOfFeed) => {}
+            other => panic!("{:?}", other),
+        }
+
+        // Entry 4 is all that is needed after entry 4. It is kept for the lipmaa link of entry 8.
+        let mut publisher = publish_many(
+            &key_pair,
+            HashAlgorithm::Blake2b,
+            0,
+            Some(4),
+            &entries[3..],
+            (5..=10).map(|seq_num| format!("message number {}", seq_num)),
+        )
+        .unwrap();
+        assert!(publisher.all(|entry| entry.is_ok()));
+
+        let mut publisher = publish_many(
+            &key_pair,
+            HashAlgorithm::Blake2b,
+            0,
+            Some(3),
+            &entries[2..3],
+            &payloads,
+        )
+        .unwrap();
+        match publisher.next() {
+            Some(Err(PublishError::PublishWithoutLipmaaEntry)) => {}
+            other => panic!("{:?}", other),
+        }
+        assert!(publisher.next().is_none());
+    }
+
+    #[test]
+    fn verify_with_policy_checks_each_limit() {
+        let mut csprng: OsRng = OsRng {};
+        let alice: Keypair = Keypair::generate(&mut csprng);
+        let bob: Keypair = Keypair::generate(&mut csprng);
+        let entries = publish_entries(&alice, HashAlgorithm::Blake2b, 3, 2);
+        let payload = b"message number 2";
+
+        let verify_second = |policy: OwnedVerifyPolicy| {
+            verify_with_policy(
+                &entries[1],
+                Some(payload),
+                Some(&entries[0]),
+                Some(&entries[0]),
+                &policy,
+            )
+        };
+
+        verify_second(VerifyPolicy::new().into_owned()).unwrap();
+        verify_second(
+            VerifyPolicy::new()
+                .max_payload_size(16)
+                .max_seq_num(2)
+                .allowed_authors([bob.public, alice.public])
+                .allowed_log_ids([0..=1, 3..=3])
+                .allow_end_of_feed(false)
+                .into_owned(),
+        )
+        .unwrap();
+
+        match verify_second(VerifyPolicy::new().max_payload_size(15).into_owned()) {
+            Err(VerifyError::PayloadTooLarge { size: 16, max: 15 }) => {}
+            other => panic!("{:?}", other),
+        }
+        match verify_second(VerifyPolicy::new().max_seq_num(1).into_owned()) {
+            Err(VerifyError::SeqNumTooLarge { seq_num: 2, max: 1 }) => {}
+            other => panic!("{:?}", other),
+        }
+        match verify_second(
+            VerifyPolicy::new()
+                .allowed_authors(vec![bob.public])
+                .into_owned(),
+        ) {
+            Err(VerifyError::AuthorNotAllowed) => {}
+            other => panic!("{:?}", other),
+        }
+        match verify_second(
+            VerifyPolicy::new()
+                .allowed_log_ids([0..=2, 4..=10])
+                .into_owned(),
+        ) {
+            Err(VerifyError::LogIdNotAllowed { log_id: 3 }) => {}
+            other => panic!("{:?}", other),
+        }
+
+        let mut out = [0u8; 512];
+        let size = publish(&mut out, &alice, 0, payload, true, None, None, None).unwrap();
+        let no_end_of_feed = VerifyPolicy::new().allow_end_of_feed(false);
+        match verify_with_policy(&out[..size], None, None, None, &no_end_of_feed) {
+            Err(VerifyError::EndOfFeedNotAllowed) => {}
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn verify_with_policy_checks_policy_before_signature() {
+        let mut csprng: OsRng = OsRng {};
+        let key_pair: Keypair = Keypair::generate(&mut csprng);
+        let mut entry = publish_entries(&key_pair, HashAlgorithm::Blake2b, 0, 1).remove(0);
+        corrupt_signature(&mut entry);
+
+        let policy = VerifyPolicy::new().max_payload_size(0);
+        match verify_with_policy(&entry, None, None, None, &policy) {
+            Err(VerifyError::PayloadTooLarge { .. }) => {}
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn verify_batch_with_policy_checks_every_entry() {
+        let mut csprng: OsRng = OsRng {};
+        let key_pair: Keypair = Keypair::generate(&mut csprng);
+        let entries_and_payloads = publish_entries(&key_pair, HashAlgorithm::Blake2b, 0, 10)
+            .into_iter()
+            .enumerate()
+            .map(|(index, bytes)| (bytes, Some(format!("message number {}", index + 1))))
+            .collect::<Vec<_>>();
+
+        verify_batch_with_policy(&entries_and_payloads, &VerifyPolicy::new().max_seq_num(10))
+            .unwrap();
+
+        match verify_batch_with_policy(&entries_and_payloads, &VerifyPolicy::new().max_seq_num(9)) {
+            Err(VerifyError::SeqNumTooLarge {
+                seq_num: 10,
+                max: 9,
+            }) => {}
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn verify_batch_report_and_sequential_with_policy_check_every_entry() {
+        let entries_and_payloads = interleaved_feeds();
+        let policy = VerifyPolicy::new().max_seq_num(4);
+
+        // The last entry of each feed has seq num 5.
+        verify_batch_report_with_policy(&entries_and_payloads, &policy)
+            .into_iter()
+            .enumerate()
+            .for_each(|(index, result)| match (index, result) {
+                (12..=14, Err(VerifyError::SeqNumTooLarge { seq_num: 5, max: 4 })) => {}
+                (0..=11, Ok(())) => {}
+                (index, other) => panic!("{}: {:?}", index, other),
+            });
+
+        match verify_batch_sequential_with_policy(&entries_and_payloads, &policy, &mut OsRng {}) {
+            Err(VerifyError::SeqNumTooLarge { seq_num: 5, max: 4 }) => {}
+            other => panic!("{:?}", other),
+        }
+        verify_batch_sequential_with_policy(
+            &entries_and_payloads,
+            &VerifyPolicy::new(),
+            &mut OsRng {},
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn strict_policy_rejects_weak_author_keys() {
+        let forged = weak_key_entry();
+        let forged_and_payload = [(&forged, Some(b"forged"))];
+        let lenient = VerifyPolicy::new();
+        let strict = VerifyPolicy::new().strict(true);
+        assert!(!lenient.is_strict());
+        assert!(strict.is_strict());
+
+        verify_with_policy(&forged, Some(b"forged"), None, None, &lenient).unwrap();
+        verify_batch_with_policy(&forged_and_payload, &lenient).unwrap();
+        verify_batch_multi_feed_with_policy(&forged_and_payload, &lenient).unwrap();
+        verify_batch_sequential_with_policy(&forged_and_payload, &lenient, &mut OsRng {}).unwrap();
+        verify_batch_report_with_policy(&forged_and_payload, &lenient)[0]
+            .as_ref()
+            .unwrap();
+
+        match verify_with_policy(&forged, Some(b"forged"), None, None, &strict) {
+            Err(VerifyError::WeakAuthorKey) => {}
+            other => panic!("{:?}", other),
+        }
+        match verify_batch_with_policy(&forged_and_payload, &strict) {
+            Err(VerifyError::WeakAuthorKey) => {}
+            other => panic!("{:?}", other),
+        }
+        match verify_batch_multi_feed_with_policy(&forged_and_payload, &strict) {
+            Err(VerifyError::WeakAuthorKey) => {}
+            other => panic!("{:?}", other),
+        }
+        match verify_batch_sequential_with_policy(&forged_and_payload, &strict, &mut OsRng {}) {
+            Err(VerifyError::WeakAuthorKey) => {}
+            other => panic!("{:?}", other),
+        }
+        match &verify_batch_report_with_policy(&forged_and_payload, &strict)[0] {
+            Err(VerifyError::WeakAuthorKey) => {}
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn verify_batch_multi_feed_with_policy_checks_every_feed() {
+        let entries_and_payloads = interleaved_feeds();
+        let alice = decode(&entries_and_payloads[0].0).unwrap().author;
+        let bob = decode(&entries_and_payloads[2].0).unwrap().author;
+
+        let both = VerifyPolicy::new()
+            .allowed_authors(vec![alice, bob])
+            .into_owned();
+        verify_batch_multi_feed_with_policy(&entries_and_payloads, &both).unwrap();
+
+        let only_alice = VerifyPolicy::new().allowed_authors([alice]);
+        match verify_batch_multi_feed_with_policy(&entries_and_payloads, &only_alice) {
+            Err(VerifyError::AuthorNotAllowed) => {}
+            other => panic!("{:?}", other),
+        }
+
+        let only_log_zero = VerifyPolicy::new().allowed_log_ids([0..=0]);
+        match verify_batch_multi_feed_with_policy(&entries_and_payloads, &only_log_zero) {
+            Err(VerifyError::LogIdNotAllowed { log_id: 1 }) => {}
+            other => panic!("{:?}", other),
+        }
+    }
 }
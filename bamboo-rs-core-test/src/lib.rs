@@ -1,2 +1,7 @@
+mod arbitrary;
+mod certificate_pool;
+mod cid;
 mod entry;
 mod signature;
+mod uri;
+mod yamf_hash;
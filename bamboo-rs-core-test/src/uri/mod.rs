@@ -0,0 +1,152 @@
+#[cfg(test)]
+mod tests {
+    use bamboo_rs_core::entry::owned::Error as OwnedEntryError;
+    use bamboo_rs_core::entry::{publish, OwnedEntry, MAX_ENTRY_SIZE};
+    use bamboo_rs_core::uri::Error;
+    use bamboo_rs_core::yamf_hash::{new_blake2b, new_blake3, Error as YamfHashError};
+    use bamboo_rs_core::{decode, Author, BambooUri, Keypair, YamfHash};
+    use rand::rngs::OsRng;
+
+    fn key_pair() -> Keypair {
+        let mut csprng: OsRng = OsRng {};
+        Keypair::generate(&mut csprng)
+    }
+
+    #[test]
+    fn yamf_hash_round_trips_through_string() {
+        for hash in [new_blake2b(b"hello"), new_blake3(b"hello")].iter() {
+            let string = hash.to_string();
+            let mut encoded = [0u8; 66];
+            let size = hash.encode(&mut encoded).unwrap();
+
+            assert_eq!(string, hex::encode(&encoded[..size]));
+            assert_eq!(string.parse::<YamfHash<_>>().unwrap(), *hash);
+        }
+    }
+
+    #[test]
+    fn yamf_hash_from_invalid_string_errors() {
+        let string = new_blake2b(b"hello").to_string();
+        let parse = |s: &str| s.parse::<YamfHash<_>>().unwrap_err();
+
+        assert!(matches!(parse("zz"), YamfHashError::DecodeHexError));
+        assert!(matches!(parse(&string[1..]), YamfHashError::DecodeHexError));
+        assert!(matches!(
+            parse(&string[..string.len() - 2]),
+            YamfHashError::DecodeError
+        ));
+        assert!(matches!(
+            parse(&format!("{}00", string)),
+            YamfHashError::DecodeError
+        ));
+    }
+
+    #[test]
+    fn entry_round_trips_through_string() {
+        let key_pair = key_pair();
+        let mut out = [0u8; MAX_ENTRY_SIZE];
+        let size = publish(&mut out, &key_pair, 0, b"hello", false, None, None, None).unwrap();
+        let entry = decode(&out[..size]).unwrap();
+
+        let string = entry.to_string();
+        assert_eq!(string, hex::encode(&out[..size]));
+
+        let parsed: OwnedEntry = string.parse().unwrap();
+        assert_eq!(parsed.as_entry(), entry);
+        assert_eq!(parsed.to_string(), string);
+
+        assert!(matches!(
+            format!("{}00", string).parse::<OwnedEntry>(),
+            Err(OwnedEntryError::EntryStringHasTrailingBytes { length: 1 })
+        ));
+        assert!(string[1..].parse::<OwnedEntry>().is_err());
+        assert!("zz".parse::<OwnedEntry>().is_err());
+        assert!("00"
+            .repeat(MAX_ENTRY_SIZE + 1)
+            .parse::<OwnedEntry>()
+            .is_err());
+    }
+
+    #[test]
+    fn author_round_trips_through_string() {
+        let author = Author(key_pair().public);
+        let string = author.to_string();
+
+        assert_eq!(string, hex::encode(author.0.as_bytes()));
+        assert_eq!(string.parse::<Author>().unwrap(), author);
+        assert_eq!(string.to_uppercase().parse::<Author>().unwrap(), author);
+        assert!(matches!(
+            string[2..].parse::<Author>(),
+            Err(Error::DecodeAuthorHex { length: 32 })
+        ));
+    }
+
+    #[test]
+    fn bamboo_uri_round_trips_through_string() {
+        let author = key_pair().public;
+        let log = BambooUri::log(author, 5);
+        let entry = BambooUri::entry(author, 5, 42);
+
+        assert_eq!(log.to_string(), format!("bamboo:{}/5", Author(author)));
+        assert_eq!(entry.to_string(), format!("bamboo:{}/5/42", Author(author)));
+        assert_eq!(log.to_string().parse::<BambooUri>().unwrap(), log);
+        assert_eq!(entry.to_string().parse::<BambooUri>().unwrap(), entry);
+        assert_eq!(entry.as_log(), log);
+    }
+
+    #[test]
+    fn bamboo_uri_of_entry() {
+        let key_pair = key_pair();
+        let mut out = [0u8; MAX_ENTRY_SIZE];
+        let size = publish(&mut out, &key_pair, 3, b"hello", false, None, None, None).unwrap();
+        let entry = decode(&out[..size]).unwrap();
+
+        assert_eq!(
+            BambooUri::from(&entry),
+            BambooUri::entry(key_pair.public, 3, 1)
+        );
+    }
+
+    #[test]
+    fn bamboo_uri_from_invalid_string_errors() {
+        let author = Author(key_pair().public);
+        let parse = |s: String| s.parse::<BambooUri>().unwrap_err();
+
+        assert!(matches!(
+            parse(format!("{}/5", author)),
+            Error::MissingScheme
+        ));
+        assert!(matches!(
+            parse(format!("bamboo:{}", author)),
+            Error::MissingLogId
+        ));
+        assert!(matches!(
+            parse(format!("bamboo:{}/-1", author)),
+            Error::InvalidLogId
+        ));
+        assert!(matches!(
+            parse(format!("bamboo:{}/+5", author)),
+            Error::InvalidLogId
+        ));
+        assert!(matches!(
+            parse(format!("bamboo:{}/5/x", author)),
+            Error::InvalidSeqNum
+        ));
+        assert!(matches!(
+            parse(format!("bamboo:{}/5/+1", author)),
+            Error::InvalidSeqNum
+        ));
+        assert!(matches!(
+            parse(format!("bamboo:{}/5/0", author)),
+            Error::ZeroSeqNum
+        ));
+        assert!(matches!(
+            parse(format!("bamboo:{}/5/1/", author)),
+            Error::TooManySegments
+        ));
+        assert!(matches!(
+            parse("bamboo:/5".to_string()),
+            Error::DecodeAuthorHex { .. }
+        ));
+    }
+}
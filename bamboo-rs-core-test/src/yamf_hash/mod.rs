@@ -0,0 +1,216 @@
+#[cfg(test)]
+mod tests {
+    use arrayvec::ArrayVec;
+    use bamboo_rs_core::yamf_hash::{
+        new_blake2b, new_blake3, Error, HashAlgorithm, YamfHash, BLAKE2B_HASH_SIZE,
+        BLAKE3_HASH_SIZE,
+    };
+    use blake2b_simd::blake2b;
+    use core::iter::FromIterator;
+
+    // The hashes of the empty string, from the BLAKE2b and BLAKE3 reference implementations.
+    const BLAKE2B_EMPTY: &str = "786a02f742015903c6c6fd852552d272912f4740e15847618a86e217f71f5419d25e1031afee585313896444934eb04b903a685b1448b755d56f701afe9be2ce";
+    const BLAKE3_EMPTY: &str = "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262";
+
+    #[test]
+    fn encode_yamf() {
+        let hash_bytes = vec![0xFF; 64];
+        let yamf_hash = YamfHash::Blake2b(hash_bytes);
+
+        let mut encoded = vec![0; 66];
+        let length = yamf_hash.encode(&mut encoded).unwrap();
+        assert_eq!(length, 66);
+        assert_eq!(encoded[0], 0);
+        assert_eq!(encoded[1], 64);
+    }
+
+    #[test]
+    fn encode_yamf_write() {
+        let hash_bytes = vec![0xFF; 64];
+        let yamf_hash = YamfHash::Blake2b(hash_bytes);
+
+        let mut encoded = Vec::new();
+        yamf_hash.encode_write(&mut encoded).unwrap();
+        assert_eq!(encoded.len(), 66);
+        assert_eq!(encoded[0], 0);
+        assert_eq!(encoded[1], 64);
+    }
+
+    #[test]
+    fn encode_yamf_not_enough_bytes_for_varu() {
+        let hash_bytes = vec![0xFF; 4];
+        let yamf_hash = YamfHash::Blake2b(hash_bytes);
+
+        let mut encoded = [0; 2];
+        match yamf_hash.encode_write(&mut encoded[..]) {
+            Err(Error::EncodeWriteError) => {}
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn encode_yamf_not_enough_bytes_for_hash() {
+        let hash_bytes = vec![0xFF; 4];
+        let yamf_hash = YamfHash::Blake2b(hash_bytes);
+
+        let mut encoded = [0; 4];
+        match yamf_hash.encode_write(&mut encoded[..]) {
+            Err(Error::EncodeWriteError) => {}
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn decode_yamf() {
+        let mut hash_bytes = vec![0xFF; 67];
+        hash_bytes[0] = 0;
+        hash_bytes[1] = 64;
+        hash_bytes[66] = 0xAA;
+        let result = YamfHash::<&[u8]>::decode(&hash_bytes);
+
+        match result {
+            Ok((YamfHash::Blake2b(vec), remaining_bytes)) => {
+                assert_eq!(vec.len(), 64);
+                assert_eq!(vec, &hash_bytes[2..66]);
+                assert_eq!(remaining_bytes, &[0xAA]);
+            }
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn decode_yamf_varu_error() {
+        let mut hash_bytes = vec![0xFF; 67];
+        hash_bytes[0] = 248;
+        hash_bytes[1] = 1;
+        hash_bytes[2] = 64;
+        hash_bytes[66] = 0xAA;
+
+        match YamfHash::<&[u8]>::decode(&hash_bytes) {
+            Err(Error::DecodeVaru64Error) => {}
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn decode_yamf_not_enough_bytes_error() {
+        let mut hash_bytes = vec![0xFF; 65];
+        hash_bytes[0] = 0;
+        hash_bytes[1] = 64;
+
+        match YamfHash::<&[u8]>::decode(&hash_bytes) {
+            Err(Error::DecodeError) => {}
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn decode_yamf_unknown_algorithm_error() {
+        let mut hash_bytes = vec![0xFF; 66];
+        hash_bytes[0] = 7;
+        hash_bytes[1] = 64;
+
+        match YamfHash::<&[u8]>::decode(&hash_bytes) {
+            Err(Error::DecodeError) => {}
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn decode_yamf_checks_the_length_byte() {
+        // A BLAKE2b header with the BLAKE3 length.
+        let mut hash_bytes = vec![0xFF; 66];
+        hash_bytes[0] = 0;
+        hash_bytes[1] = BLAKE3_HASH_SIZE as u8;
+
+        match YamfHash::<&[u8]>::decode(&hash_bytes) {
+            Err(Error::DecodeLengthError) => {}
+            other => panic!("{:?}", other),
+        }
+
+        // A BLAKE3 header with the BLAKE2b length.
+        hash_bytes[0] = 1;
+        hash_bytes[1] = BLAKE2B_HASH_SIZE as u8;
+
+        match YamfHash::<&[u8]>::decode(&hash_bytes) {
+            Err(Error::DecodeLengthError) => {}
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn decode_yamf_missing_length_error() {
+        match YamfHash::<&[u8]>::decode(&[0]) {
+            Err(Error::DecodeVaru64Error) => {}
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn yamf_encoding_vectors() {
+        let vectors = [
+            (new_blake2b(b""), format!("0040{}", BLAKE2B_EMPTY)),
+            (new_blake3(b""), format!("0120{}", BLAKE3_EMPTY)),
+        ];
+
+        for (hash, encoded) in vectors.iter() {
+            assert_eq!(hash.to_string(), *encoded);
+
+            let bytes = hex::decode(encoded).unwrap();
+            let (decoded, remaining_bytes) = YamfHash::<&[u8]>::decode(&bytes).unwrap();
+            assert_eq!(decoded, *hash);
+            assert!(remaining_bytes.is_empty());
+            assert_eq!(decoded.encoding_length(), bytes.len());
+
+            let mut out = [0u8; 66];
+            let size = hash.encode(&mut out).unwrap();
+            assert_eq!(&out[..size], &bytes[..]);
+        }
+    }
+
+    #[test]
+    fn decode_owned_round_trips() {
+        for algorithm in [HashAlgorithm::Blake2b, HashAlgorithm::Blake3].iter() {
+            let hash = algorithm.hash(b"hello bamboo!");
+
+            let mut encoded = Vec::new();
+            hash.encode_write(&mut encoded).unwrap();
+            encoded.push(0xAA);
+
+            let (decoded, remaining_bytes) = YamfHash::<&[u8]>::decode_owned(&encoded).unwrap();
+            assert_eq!(decoded, hash);
+            assert_eq!(decoded.algorithm(), *algorithm);
+            assert_eq!(remaining_bytes, &[0xAA]);
+        }
+    }
+
+    #[test]
+    fn blake_yamf_hash_eq() {
+        let hash_bytes = blake2b(&[1, 2]);
+        let vec_bytes: ArrayVec<[u8; BLAKE2B_HASH_SIZE]> =
+            ArrayVec::from_iter(hash_bytes.as_bytes().iter().copied());
+        let result = YamfHash::Blake2b(vec_bytes);
+
+        let result2 = YamfHash::Blake2b(hash_bytes.as_bytes());
+
+        assert_eq!(result, result2);
+        assert_eq!(result2, result);
+        assert_eq!(result, new_blake2b(&[1, 2]));
+    }
+
+    #[test]
+    fn from_owned_to_ref_yamf_hash() {
+        let result = new_blake2b(&[1, 2]);
+        let borrowed: YamfHash<&[u8]> = YamfHash::from(&result);
+
+        assert_eq!(borrowed, result);
+    }
+
+    #[test]
+    fn hashes_of_different_algorithms_are_not_equal() {
+        let blake2b_hash = new_blake2b(b"hello");
+        let blake3_hash = HashAlgorithm::Blake3.with_bytes(&blake2b_hash.as_bytes()[..32]);
+
+        assert_ne!(blake2b_hash.as_borrowed(), blake3_hash);
+    }
+}
@@ -0,0 +1,496 @@
+//! Generators for fuzzing and property testing code that handles bamboo entries. Requires the
+//! `arbitrary` feature.
+//!
+//! - [OwnedEntry] implements `Arbitrary` with entries that encode, decode and have a valid
+//!   signature, but whose links point at nothing.
+//! - [ArbitraryLog] is a signed chain of entries with correct backlinks and lipmaa links.
+//! - [CorruptedEntry] is an entry and the arguments to [verify] that make it fail with the error
+//!   its [Corruption] names.
+//!
+//! Everything is derived from the fuzzer's bytes, including the keys, so failures reproduce.
+use ::arbitrary::{Arbitrary, Result, Unstructured};
+use alloc::vec::Vec;
+use arrayvec::ArrayVec;
+use core::iter::FromIterator;
+use ed25519_dalek::{Keypair, PublicKey, SecretKey, Signer};
+use lipmaa_link::lipmaa;
+
+use crate::entry::decode::Error as DecodeError;
+use crate::entry::publish::{
+    publish_many, publish_with_links_and_hash_algorithm, publish_with_payload_hash,
+};
+use crate::entry::verify::{verify, Error as VerifyError};
+use crate::entry::{
+    is_lipmaa_required, EntryBuilder, OwnedEntry, MAX_ENTRY_SIZE, PUBLIC_KEY_LENGTH,
+};
+use crate::signature::{Signature, ED25519_SIGNATURE_SIZE};
+use crate::yamf_hash::{HashAlgorithm, YamfHash, BLAKE2B_HASH_SIZE};
+
+/// The longest [ArbitraryLog] made by its `Arbitrary` impl.
+pub const MAX_ARBITRARY_LOG_LEN: u64 = 64;
+
+impl<'a> Arbitrary<'a> for HashAlgorithm {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        u.choose(&[
+            HashAlgorithm::Blake2b,
+            #[cfg(feature = "blake3")]
+            HashAlgorithm::Blake3,
+        ])
+        .copied()
+    }
+}
+
+impl<'a> Arbitrary<'a> for OwnedEntry {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let key_pair = arbitrary_key_pair(u)?;
+        let hash_algorithm: HashAlgorithm = u.arbitrary()?;
+        let seq_num = u.int_in_range(1..=u64::MAX)?;
+
+        let mut arbitrary_hash = || -> Result<_> {
+            let bytes: [u8; BLAKE2B_HASH_SIZE] = u.arbitrary()?;
+            let digest = bytes[..hash_algorithm.hash_size()].iter().copied();
+            Ok(hash_algorithm.with_bytes(ArrayVec::from_iter(digest)))
+        };
+        let payload_hash = arbitrary_hash()?;
+        let backlink = if seq_num > 1 {
+            Some(arbitrary_hash()?)
+        } else {
+            None
+        };
+        let lipmaa_link = if seq_num > 1 && is_lipmaa_required(seq_num) {
+            Some(arbitrary_hash()?)
+        } else {
+            None
+        };
+
+        let mut builder = EntryBuilder::new()
+            .log_id(u.arbitrary()?)
+            .is_end_of_feed(u.arbitrary()?)
+            .payload_hash(payload_hash, u.arbitrary()?)
+            .author(key_pair.public)
+            .seq_num(seq_num);
+
+        if let Some(backlink) = backlink {
+            builder = builder.backlink(backlink);
+        }
+        if let Some(lipmaa_link) = lipmaa_link {
+            builder = builder.lipmaa_link(lipmaa_link);
+        }
+
+        let mut entry = builder
+            .build()
+            .expect("arbitrary entry has the links its seq num needs");
+
+        let mut out = [0u8; MAX_ENTRY_SIZE];
+        let size = entry
+            .as_entry()
+            .encode(&mut out)
+            .expect("arbitrary entry encodes");
+        let signature = key_pair.sign(&out[..size]);
+        entry.sig = Some(Signature(ArrayVec::from(signature.to_bytes())));
+
+        Ok(entry)
+    }
+}
+
+/// A signed log of entries with correct links, published by [publish_many].
+#[derive(Debug)]
+pub struct ArbitraryLog {
+    pub key_pair: Keypair,
+    pub hash_algorithm: HashAlgorithm,
+    pub log_id: u64,
+    /// The payload of each entry, `payloads[0]` is the payload of seq num `1`.
+    pub payloads: Vec<Vec<u8>>,
+    /// The encoded entries, `entries[0]` is seq num `1`.
+    pub entries: Vec<Vec<u8>>,
+}
+
+impl ArbitraryLog {
+    /// An arbitrary log with exactly `len` entries.
+    pub fn with_len(u: &mut Unstructured, len: u64) -> Result<ArbitraryLog> {
+        let key_pair = arbitrary_key_pair(u)?;
+        let hash_algorithm = u.arbitrary()?;
+        let log_id = u.arbitrary()?;
+        let payloads = (0..len)
+            .map(|_| u.arbitrary())
+            .collect::<Result<Vec<Vec<u8>>>>()?;
+
+        let entries = publish_many(
+            &key_pair,
+            hash_algorithm,
+            log_id,
+            None,
+            None::<&[u8]>,
+            &payloads,
+        )
+        .expect("a new log needs no ancestors")
+        .collect::<Result<Vec<_>, _>>()
+        .expect("arbitrary log publishes");
+
+        Ok(ArbitraryLog {
+            key_pair,
+            hash_algorithm,
+            log_id,
+            payloads,
+            entries,
+        })
+    }
+
+    /// The encoded entry `seq_num`.
+    pub fn entry(&self, seq_num: u64) -> Option<&[u8]> {
+        let index = seq_num.checked_sub(1)? as usize;
+        self.entries.get(index).map(Vec::as_slice)
+    }
+
+    /// The payload of entry `seq_num`.
+    pub fn payload(&self, seq_num: u64) -> Option<&[u8]> {
+        let index = seq_num.checked_sub(1)? as usize;
+        self.payloads.get(index).map(Vec::as_slice)
+    }
+
+    /// The encoded lipmaa entry of entry `seq_num`, `None` for the first entry.
+    pub fn lipmaa_entry(&self, seq_num: u64) -> Option<&[u8]> {
+        self.entry(seq_num)?;
+        self.entry(lipmaa(seq_num)).filter(|_| seq_num > 1)
+    }
+
+    /// The encoded backlink entry of entry `seq_num`, `None` for the first entry.
+    pub fn backlink_entry(&self, seq_num: u64) -> Option<&[u8]> {
+        self.entry(seq_num)?;
+        self.entry(seq_num - 1)
+    }
+}
+
+impl<'a> Arbitrary<'a> for ArbitraryLog {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let len = u.int_in_range(1..=MAX_ARBITRARY_LOG_LEN)?;
+        ArbitraryLog::with_len(u, len)
+    }
+}
+
+/// The ways a [CorruptedEntry] can fail [verify], named after the error it fails with.
+///
+/// Errors that [verify] can't return, like the ones only strict verification or reading a
+/// payload can cause, don't have a corruption.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Corruption {
+    DecodeEntry,
+    DecodeSignature,
+    InvalidSignature,
+    PayloadHashDidNotMatch,
+    PayloadLengthDidNotMatch,
+    LipmaaHashDoesNotMatch,
+    DecodeLipmaaEntry,
+    LipmaaLogIdDoesNotMatch,
+    LipmaaAuthorDoesNotMatch,
+    LipmaaLinkRequired,
+    DecodeBacklinkEntry,
+    BacklinkLogIdDoesNotMatch,
+    BacklinkAuthorDoesNotMatch,
+    PublishedAfterEndOfFeed,
+    BacklinkHashDoesNotMatch,
+}
+
+impl Corruption {
+    pub const ALL: [Corruption; 15] = [
+        Corruption::DecodeEntry,
+        Corruption::DecodeSignature,
+        Corruption::InvalidSignature,
+        Corruption::PayloadHashDidNotMatch,
+        Corruption::PayloadLengthDidNotMatch,
+        Corruption::LipmaaHashDoesNotMatch,
+        Corruption::DecodeLipmaaEntry,
+        Corruption::LipmaaLogIdDoesNotMatch,
+        Corruption::LipmaaAuthorDoesNotMatch,
+        Corruption::LipmaaLinkRequired,
+        Corruption::DecodeBacklinkEntry,
+        Corruption::BacklinkLogIdDoesNotMatch,
+        Corruption::BacklinkAuthorDoesNotMatch,
+        Corruption::PublishedAfterEndOfFeed,
+        Corruption::BacklinkHashDoesNotMatch,
+    ];
+
+    /// Is `error` the error this corruption causes?
+    pub fn matches(self, error: &VerifyError) -> bool {
+        matches!(
+            (self, error),
+            (Corruption::DecodeEntry, VerifyError::DecodeEntry { .. })
+                | (
+                    Corruption::DecodeSignature,
+                    VerifyError::DecodeEntry {
+                        source: DecodeError::DecodeSigError { .. }
+                    }
+                )
+                | (Corruption::InvalidSignature, VerifyError::InvalidSignature)
+                | (
+                    Corruption::PayloadHashDidNotMatch,
+                    VerifyError::PayloadHashDidNotMatch {}
+                )
+                | (
+                    Corruption::PayloadLengthDidNotMatch,
+                    VerifyError::PayloadLengthDidNotMatch { .. }
+                )
+                | (
+                    Corruption::LipmaaHashDoesNotMatch,
+                    VerifyError::LipmaaHashDoesNotMatch {}
+                )
+                | (
+                    Corruption::DecodeLipmaaEntry,
+                    VerifyError::DecodeLipmaaEntry { .. }
+                )
+                | (
+                    Corruption::LipmaaLogIdDoesNotMatch,
+                    VerifyError::LipmaaLogIdDoesNotMatch { .. }
+                )
+                | (
+                    Corruption::LipmaaAuthorDoesNotMatch,
+                    VerifyError::LipmaaAuthorDoesNotMatch {}
+                )
+                | (
+                    Corruption::LipmaaLinkRequired,
+                    VerifyError::LipmaaLinkRequired
+                )
+                | (
+                    Corruption::DecodeBacklinkEntry,
+                    VerifyError::DecodeBacklinkEntry { .. }
+                )
+                | (
+                    Corruption::BacklinkLogIdDoesNotMatch,
+                    VerifyError::BacklinkLogIdDoesNotMatch { .. }
+                )
+                | (
+                    Corruption::BacklinkAuthorDoesNotMatch,
+                    VerifyError::BacklinkAuthorDoesNotMatch
+                )
+                | (
+                    Corruption::PublishedAfterEndOfFeed,
+                    VerifyError::PublishedAfterEndOfFeed
+                )
+                | (
+                    Corruption::BacklinkHashDoesNotMatch,
+                    VerifyError::BacklinkHashDoesNotMatch {}
+                )
+        )
+    }
+}
+
+impl<'a> Arbitrary<'a> for Corruption {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        u.choose(&Corruption::ALL).copied()
+    }
+}
+
+/// An entry and the arguments to [verify] it with that fail with its [Corruption]'s error.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CorruptedEntry {
+    pub corruption: Corruption,
+    pub entry_bytes: Vec<u8>,
+    pub payload: Option<Vec<u8>>,
+    pub lipmaa_link: Option<Vec<u8>>,
+    pub backlink: Option<Vec<u8>>,
+}
+
+impl CorruptedEntry {
+    /// An arbitrary entry with the `corruption`.
+    pub fn with_corruption(u: &mut Unstructured, corruption: Corruption) -> Result<CorruptedEntry> {
+        let key_pair = arbitrary_key_pair(u)?;
+        // Derived from the first so they differ even when the fuzzer runs out of bytes.
+        let other_key_pair = other_key_pair(&key_pair);
+        let hash_algorithm: HashAlgorithm = u.arbitrary()?;
+        let log_id: u64 = u.arbitrary()?;
+        let other_log_id = log_id.wrapping_add(1);
+        let payload: Vec<u8> = u.arbitrary()?;
+        let other_payload = [&payload[..], &[0]].concat();
+
+        let first = |key_pair, log_id, payload: &[u8], is_end_of_feed| {
+            publish_linked(
+                key_pair,
+                hash_algorithm,
+                log_id,
+                payload,
+                is_end_of_feed,
+                1,
+                None,
+                None,
+            )
+        };
+        // Entry 2 only has a backlink, entry 4 has a lipmaa link to entry 1 as well.
+        let second = |backlink: &[u8]| {
+            publish_linked(
+                &key_pair,
+                hash_algorithm,
+                log_id,
+                &payload,
+                false,
+                2,
+                None,
+                Some(backlink),
+            )
+        };
+        let fourth = |lipmaa_link: &[u8]| {
+            publish_linked(
+                &key_pair,
+                hash_algorithm,
+                log_id,
+                &payload,
+                false,
+                4,
+                Some(lipmaa_link),
+                Some(lipmaa_link),
+            )
+        };
+
+        let entry = first(&key_pair, log_id, &payload, false);
+        let other_author_entry = first(&other_key_pair, log_id, &payload, false);
+        let other_log_entry = first(&key_pair, other_log_id, &payload, false);
+        let other_entry = first(&key_pair, log_id, &other_payload, false);
+        let end_of_feed_entry = first(&key_pair, log_id, &payload, true);
+
+        let corrupted = |entry_bytes, payload: Option<&[u8]>, lipmaa_link, backlink| {
+            Ok(CorruptedEntry {
+                corruption,
+                entry_bytes,
+                payload: payload.map(<[u8]>::to_vec),
+                lipmaa_link,
+                backlink,
+            })
+        };
+
+        match corruption {
+            Corruption::DecodeEntry => {
+                let truncated = entry[..PUBLIC_KEY_LENGTH].to_vec();
+                corrupted(truncated, None, None, None)
+            }
+            Corruption::DecodeSignature => {
+                let truncated = entry[..entry.len() - ED25519_SIGNATURE_SIZE / 2].to_vec();
+                corrupted(truncated, None, None, None)
+            }
+            Corruption::InvalidSignature => {
+                let mut entry = entry;
+                let last = entry.len() - 1;
+                entry[last] ^= 1;
+                corrupted(entry, None, None, None)
+            }
+            Corruption::PayloadHashDidNotMatch => {
+                corrupted(entry, Some(&other_payload), None, None)
+            }
+            Corruption::PayloadLengthDidNotMatch => {
+                let mut out = [0u8; MAX_ENTRY_SIZE];
+                let size = publish_with_payload_hash(
+                    &mut out,
+                    &key_pair,
+                    log_id,
+                    &hash_algorithm.hash(&payload),
+                    payload.len() as u64 + 1,
+                    false,
+                    None,
+                    None,
+                    None,
+                )
+                .expect("arbitrary entry publishes");
+                corrupted(out[..size].to_vec(), Some(&payload), None, None)
+            }
+            Corruption::LipmaaHashDoesNotMatch => {
+                corrupted(fourth(&entry), None, Some(other_entry), None)
+            }
+            Corruption::DecodeLipmaaEntry => corrupted(fourth(&[]), None, Some(Vec::new()), None),
+            Corruption::LipmaaLogIdDoesNotMatch => {
+                corrupted(fourth(&other_log_entry), None, Some(other_log_entry), None)
+            }
+            Corruption::LipmaaAuthorDoesNotMatch => corrupted(
+                fourth(&other_author_entry),
+                None,
+                Some(other_author_entry),
+                None,
+            ),
+            Corruption::LipmaaLinkRequired => corrupted(fourth(&entry), None, None, None),
+            Corruption::DecodeBacklinkEntry => {
+                corrupted(second(&entry), None, None, Some(Vec::new()))
+            }
+            Corruption::BacklinkLogIdDoesNotMatch => {
+                corrupted(second(&entry), None, None, Some(other_log_entry))
+            }
+            Corruption::BacklinkAuthorDoesNotMatch => {
+                corrupted(second(&entry), None, None, Some(other_author_entry))
+            }
+            Corruption::PublishedAfterEndOfFeed => corrupted(
+                second(&end_of_feed_entry),
+                None,
+                None,
+                Some(end_of_feed_entry),
+            ),
+            Corruption::BacklinkHashDoesNotMatch => {
+                corrupted(second(&entry), None, None, Some(other_entry))
+            }
+        }
+    }
+
+    /// [verify] the entry with the corrupted arguments.
+    pub fn verify(&self) -> Result<(), VerifyError> {
+        verify(
+            &self.entry_bytes,
+            self.payload.as_deref(),
+            self.lipmaa_link.as_deref(),
+            self.backlink.as_deref(),
+        )
+    }
+}
+
+impl<'a> Arbitrary<'a> for CorruptedEntry {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let corruption = u.arbitrary()?;
+        CorruptedEntry::with_corruption(u, corruption)
+    }
+}
+
+fn arbitrary_key_pair(u: &mut Unstructured) -> Result<Keypair> {
+    let secret_bytes: [u8; 32] = u.arbitrary()?;
+    Ok(key_pair_from_secret(secret_bytes))
+}
+
+fn other_key_pair(key_pair: &Keypair) -> Keypair {
+    let mut secret_bytes = key_pair.secret.to_bytes();
+    secret_bytes[0] ^= 1;
+    key_pair_from_secret(secret_bytes)
+}
+
+fn key_pair_from_secret(secret_bytes: [u8; 32]) -> Keypair {
+    let secret = SecretKey::from_bytes(&secret_bytes).expect("any 32 bytes are a secret key");
+    let public = PublicKey::from(&secret);
+    Keypair { secret, public }
+}
+
+// Publish entry `seq_num` linking to the hashes of `lipmaa_link` and `backlink`, whatever they
+// are, so the links can be wrong on purpose.
+#[allow(clippy::too_many_arguments)]
+fn publish_linked(
+    key_pair: &Keypair,
+    hash_algorithm: HashAlgorithm,
+    log_id: u64,
+    payload: &[u8],
+    is_end_of_feed: bool,
+    seq_num: u64,
+    lipmaa_link: Option<&[u8]>,
+    backlink: Option<&[u8]>,
+) -> Vec<u8> {
+    let hash = |bytes| hash_algorithm.hash(bytes);
+    let lipmaa_link: Option<YamfHash<_>> = lipmaa_link.map(hash);
+    let backlink = backlink.map(hash);
+
+    let mut out = [0u8; MAX_ENTRY_SIZE];
+    let size = publish_with_links_and_hash_algorithm(
+        &mut out,
+        key_pair,
+        hash_algorithm,
+        log_id,
+        payload,
+        is_end_of_feed,
+        Some(seq_num - 1).filter(|seq_num| *seq_num > 0),
+        lipmaa_link.as_ref(),
+        backlink.as_ref(),
+        false,
+    )
+    .expect("arbitrary entry publishes");
+
+    out[..size].to_vec()
+}
@@ -0,0 +1,16 @@
+use snafu::Snafu;
+
+#[derive(Debug, Clone, Snafu)]
+#[snafu(visibility = "pub(super)")]
+pub enum Error {
+    #[snafu(display("Sequence numbers start at 1 but 0 was used"))]
+    ZeroSeqNum,
+    #[snafu(display(
+        "A lipmaa path goes from a newer to an older entry, but {} is older than {}",
+        from,
+        to
+    ))]
+    PathToNewerEntry { from: u64, to: u64 },
+}
+
+pub type Result<T, E = Error> = core::result::Result<T, E>;
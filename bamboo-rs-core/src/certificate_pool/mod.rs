@@ -0,0 +1,104 @@
+//! Sequence numbers of the entries needed to verify an entry without its whole log.
+//!
+//! From the [spec](https://github.com/AljoschaMeyer/bamboo#partial-replication), the certificate
+//! pool of entry `n` is the shortest path of links from `n` to the first entry, plus the shortest
+//! path to `n` from the smallest `(3^k - 1) / 2` that is `>= n`. Any two certificate pools of a
+//! log share an entry, so a peer holding only certificate pools can still verify everything it
+//! has against everything else it's sent.
+//!
+//! All the iterators count down, from the newest seq num to the oldest.
+use core::iter::{FusedIterator, Skip};
+use lipmaa_link::lipmaa;
+use snafu::ensure;
+
+pub mod error;
+pub use error::*;
+
+/// The shortest path of links from `from` back to `to`.
+///
+/// Each step takes the lipmaa link, unless that would jump past `to`, in which case it takes the
+/// backlink. Includes both `from` and `to`.
+pub fn lipmaa_path(from: u64, to: u64) -> Result<LipmaaPath> {
+    ensure!(to > 0, ZeroSeqNum);
+    ensure!(from >= to, PathToNewerEntry { from, to });
+
+    Ok(LipmaaPath {
+        next: Some(from),
+        to,
+    })
+}
+
+/// The certificate pool of `seq_num`, the seq nums of the entries needed to verify it.
+pub fn certificate_pool(seq_num: u64) -> Result<CertificatePool> {
+    ensure!(seq_num > 0, ZeroSeqNum);
+
+    // The anchor doesn't fit in a u64 for the very largest seq nums. No entry can follow those,
+    // so the path from it isn't needed.
+    let anchor = next_anchor(seq_num).unwrap_or(seq_num);
+
+    Ok(CertificatePool {
+        to_seq_num: lipmaa_path(anchor, seq_num)?,
+        to_first: lipmaa_path(seq_num, 1)?.skip(1),
+    })
+}
+
+/// The smallest `(3^k - 1) / 2` that is `>= seq_num`.
+fn next_anchor(seq_num: u64) -> Option<u64> {
+    let mut power_of_three: u128 = 3;
+    let mut anchor: u128 = 1;
+
+    while anchor < seq_num as u128 {
+        power_of_three *= 3;
+        anchor = (power_of_three - 1) / 2;
+    }
+
+    if anchor > u64::MAX as u128 {
+        None
+    } else {
+        Some(anchor as u64)
+    }
+}
+
+/// Iterator of seq nums returned by [lipmaa_path].
+#[derive(Debug, Clone)]
+pub struct LipmaaPath {
+    next: Option<u64>,
+    to: u64,
+}
+
+impl Iterator for LipmaaPath {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        let current = self.next?;
+
+        self.next = if current == self.to {
+            None
+        } else if lipmaa(current) >= self.to {
+            Some(lipmaa(current))
+        } else {
+            Some(current - 1)
+        };
+
+        Some(current)
+    }
+}
+
+impl FusedIterator for LipmaaPath {}
+
+/// Iterator of seq nums returned by [certificate_pool].
+#[derive(Debug, Clone)]
+pub struct CertificatePool {
+    to_seq_num: LipmaaPath,
+    to_first: Skip<LipmaaPath>,
+}
+
+impl Iterator for CertificatePool {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        self.to_seq_num.next().or_else(|| self.to_first.next())
+    }
+}
+
+impl FusedIterator for CertificatePool {}
@@ -0,0 +1,17 @@
+use crate::entry::decode::Error as EntryDecodeError;
+use snafu::Snafu;
+
+#[derive(Debug, Snafu)]
+#[snafu(visibility = "pub(super)")]
+pub enum Error {
+    #[snafu(display("Multihash code {:#x} is not a supported yamf hash algorithm", code))]
+    UnsupportedMultihashCode { code: u64 },
+    #[snafu(display("Multihash digest must be {} bytes but was {} bytes", expected, length))]
+    InvalidDigestLength { length: usize, expected: usize },
+    #[snafu(display("Could not decode entry to find its hash algorithm: {}", source))]
+    DecodeCidEntry { source: EntryDecodeError },
+    #[snafu(display("Entry was followed by {} trailing bytes", length))]
+    CidEntryHasTrailingBytes { length: usize },
+}
+
+pub type Result<T, E = Error> = core::result::Result<T, E>;
@@ -0,0 +1,106 @@
+//! Convert [YamfHash]es to and from [multihashes](https://multiformats.io/multihash/) and build
+//! [CIDs](https://github.com/multiformats/cid) for entries and payloads. Requires the `cid`
+//! feature.
+//!
+//! Bamboo links entries by the hash of their encoding, so the CID of an entry is a CIDv1 with the
+//! `raw` codec over the same hash a later entry uses as its backlink or lipmaa link.
+use arrayvec::ArrayVec;
+use core::borrow::Borrow;
+use core::convert::TryFrom;
+use core::iter::FromIterator;
+use snafu::{ensure, OptionExt, ResultExt};
+
+use crate::entry::decode::decode_with_length;
+use crate::yamf_hash::{HashAlgorithm, YamfHash, BLAKE2B_HASH_SIZE};
+
+pub mod error;
+pub use error::*;
+
+pub use ::cid::multihash::Multihash;
+pub use ::cid::Cid;
+
+/// The multicodec code of a BLAKE2b-512 multihash.
+pub const BLAKE2B_512_MULTIHASH_CODE: u64 = 0xb240;
+/// The multicodec code of a BLAKE3 multihash.
+pub const BLAKE3_MULTIHASH_CODE: u64 = 0x1e;
+/// The multicodec code of the `raw` codec, used for the CIDs of entries and payloads.
+pub const RAW_CODEC: u64 = 0x55;
+
+impl HashAlgorithm {
+    /// The multicodec code of multihashes made with this algorithm.
+    pub fn multihash_code(self) -> u64 {
+        match self {
+            HashAlgorithm::Blake2b => BLAKE2B_512_MULTIHASH_CODE,
+            #[cfg(feature = "blake3")]
+            HashAlgorithm::Blake3 => BLAKE3_MULTIHASH_CODE,
+        }
+    }
+
+    /// The algorithm for a multihash code, if it's supported.
+    pub fn from_multihash_code(code: u64) -> Option<HashAlgorithm> {
+        match code {
+            BLAKE2B_512_MULTIHASH_CODE => Some(HashAlgorithm::Blake2b),
+            #[cfg(feature = "blake3")]
+            BLAKE3_MULTIHASH_CODE => Some(HashAlgorithm::Blake3),
+            _ => None,
+        }
+    }
+}
+
+impl<'a, T: Borrow<[u8]>> From<&'a YamfHash<T>> for Multihash<BLAKE2B_HASH_SIZE> {
+    fn from(hash: &'a YamfHash<T>) -> Self {
+        // Every algorithm's digest fits in a BLAKE2b sized multihash.
+        Multihash::wrap(hash.algorithm().multihash_code(), hash.as_bytes()).unwrap()
+    }
+}
+
+impl<'a, const S: usize> TryFrom<&'a Multihash<S>> for YamfHash<ArrayVec<[u8; BLAKE2B_HASH_SIZE]>> {
+    type Error = Error;
+
+    fn try_from(multihash: &'a Multihash<S>) -> Result<Self> {
+        let code = multihash.code();
+        let algorithm =
+            HashAlgorithm::from_multihash_code(code).context(UnsupportedMultihashCode { code })?;
+
+        let digest = multihash.digest();
+        ensure!(
+            digest.len() == algorithm.hash_size(),
+            InvalidDigestLength {
+                length: digest.len(),
+                expected: algorithm.hash_size()
+            }
+        );
+
+        Ok(algorithm.with_bytes(ArrayVec::from_iter(digest.iter().copied())))
+    }
+}
+
+/// The `raw` CIDv1 of the bytes `hash` was made from.
+///
+/// Get the hash back with `YamfHash::try_from(cid.hash())`.
+pub fn cid_from_yamf_hash<T: Borrow<[u8]>>(hash: &YamfHash<T>) -> Cid {
+    Cid::new_v1(RAW_CODEC, hash.into())
+}
+
+/// The CID of a payload hashed with `hash_algorithm`.
+pub fn payload_cid(payload: &[u8], hash_algorithm: HashAlgorithm) -> Cid {
+    cid_from_yamf_hash(&hash_algorithm.hash(payload))
+}
+
+/// The CID of an encoded entry.
+///
+/// The entry is hashed with the same algorithm as its payload, which is the hash later entries in
+/// its log use to link to it. `entry_bytes` must hold exactly one entry, any bytes after it would
+/// change the hash.
+pub fn entry_cid(entry_bytes: &[u8]) -> Result<Cid> {
+    let (entry, length) = decode_with_length(entry_bytes).context(DecodeCidEntry)?;
+    ensure!(
+        length == entry_bytes.len(),
+        CidEntryHasTrailingBytes {
+            length: entry_bytes.len() - length
+        }
+    );
+    let hash_algorithm = entry.payload_hash.algorithm();
+
+    Ok(cid_from_yamf_hash(&hash_algorithm.hash(entry_bytes)))
+}
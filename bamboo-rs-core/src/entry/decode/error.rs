@@ -1,6 +1,6 @@
 use crate::signature::error::Error as SigError;
+use crate::yamf_hash::error::Error as YamfHashError;
 use snafu::Snafu;
-use yamf_hash::error::Error as YamfHashError;
 
 #[derive(Debug, Snafu)]
 #[snafu(visibility = "pub(super)")]
@@ -25,6 +25,26 @@ pub enum Error {
     DecodeSigError { source: SigError },
     #[snafu(display("Bytes to decode had length of 0"))]
     DecodeInputIsLengthZero,
+    #[snafu(display("End of feed tag must be 0 or 1 but was {}", tag))]
+    DecodeEndOfFeedTagInvalid { tag: u8 },
+    #[snafu(display("Non canonical varu64 encoding of {} for {}", value, field))]
+    DecodeNonCanonicalVaru64 { field: &'static str, value: u64 },
+    #[snafu(display("Entry was followed by {} trailing bytes", length))]
+    DecodeTrailingBytes { length: usize },
+    #[snafu(display("Entry does not re-encode to the bytes it was decoded from"))]
+    DecodeEncodingNotCanonical,
+    #[snafu(display("The author key is a weak ed25519 key"))]
+    DecodeWeakAuthorKey,
 }
 
 pub type Result<T, E = Error> = core::result::Result<T, E>;
+
+#[cfg(feature = "std")]
+#[derive(Debug, Snafu)]
+#[snafu(visibility = "pub(super)")]
+pub enum ReadError {
+    #[snafu(display("Could not read entry bytes: {}", source))]
+    ReadEntryBytes { source: std::io::Error },
+    #[snafu(display("Could not decode entry at offset {}: {}", offset, source))]
+    ReadEntryDecode { source: Error, offset: u64 },
+}
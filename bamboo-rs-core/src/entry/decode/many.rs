@@ -0,0 +1,74 @@
+use core::ops::Range;
+
+use super::{decode_with_length, Entry, Error};
+
+/// An [Entry] decoded by [DecodeMany], along with where it was found in the input.
+#[derive(Debug, Eq, PartialEq)]
+pub struct DecodedEntry<'a> {
+    /// The decoded entry. References the input bytes.
+    pub entry: Entry<&'a [u8], &'a [u8]>,
+    /// The encoded bytes of this entry.
+    pub bytes: &'a [u8],
+    /// The range of the input that `bytes` occupies.
+    pub range: Range<usize>,
+}
+
+/// Iterator over back-to-back encoded entries. Created by [decode_many].
+///
+/// Yields an `Err` and then stops if the remaining bytes can't be decoded as an [Entry].
+#[derive(Debug)]
+pub struct DecodeMany<'a> {
+    bytes: &'a [u8],
+    consumed: usize,
+    failed: bool,
+}
+
+impl<'a> DecodeMany<'a> {
+    /// The number of bytes that have been decoded so far.
+    pub fn consumed(&self) -> usize {
+        self.consumed
+    }
+
+    /// The bytes that have not been decoded yet.
+    pub fn remaining(&self) -> &'a [u8] {
+        &self.bytes[self.consumed..]
+    }
+}
+
+impl<'a> Iterator for DecodeMany<'a> {
+    type Item = Result<DecodedEntry<'a>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed || self.consumed == self.bytes.len() {
+            return None;
+        }
+
+        match decode_with_length(self.remaining()) {
+            Ok((entry, length)) => {
+                let range = self.consumed..self.consumed + length;
+                self.consumed += length;
+
+                Some(Ok(DecodedEntry {
+                    entry,
+                    bytes: &self.bytes[range.clone()],
+                    range,
+                }))
+            }
+            Err(err) => {
+                self.failed = true;
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+/// Decode `bytes` as a sequence of concatenated entries.
+///
+/// Returned entries reference `bytes`.
+pub fn decode_many<'a>(bytes: &'a [u8]) -> DecodeMany<'a> {
+    DecodeMany {
+        bytes,
+        consumed: 0,
+        failed: false,
+    }
+}
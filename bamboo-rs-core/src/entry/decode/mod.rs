@@ -1,22 +1,82 @@
-use varu64::decode as varu64_decode;
+use varu64::{decode as varu64_decode, DecodeError as Varu64DecodeError};
 
 use ed25519_dalek::{PublicKey as DalekPublicKey, PUBLIC_KEY_LENGTH};
 
+use crate::entry::verify::is_weak_key;
 use crate::signature::Signature;
 use crate::yamf_hash::YamfHash;
 
-use super::{is_lipmaa_required, Entry};
-use snafu::{ensure, NoneError, ResultExt};
+use super::{is_lipmaa_required, Entry, MAX_ENTRY_SIZE};
+use snafu::{ensure, ResultExt};
 
 pub mod error;
 pub use error::*;
 
+pub mod many;
+pub use many::{decode_many, DecodeMany, DecodedEntry};
+
+#[cfg(feature = "std")]
+pub mod reader;
+#[cfg(feature = "std")]
+pub use reader::{EntryReader, ReadEntry};
+
+/// An [Entry] decoded by [decode_with_length], and the number of bytes it used.
+pub type EntryWithLength<'a> = (Entry<&'a [u8], &'a [u8]>, usize);
+
 /// Try and decode `bytes` as an [Entry].
 ///
-/// Returned [Entry] references `bytes`.
+/// Returned [Entry] references `bytes`. Any bytes following the signature are ignored, use
+/// [decode_with_length] if you need to know where the entry ends.
 pub fn decode<'a>(bytes: &'a [u8]) -> Result<Entry<&'a [u8], &'a [u8]>, Error> {
+    decode_with_length(bytes).map(|(entry, _)| entry)
+}
+
+/// Try and decode `bytes` as an [Entry], also returning the number of bytes the entry used.
+///
+/// Returned [Entry] references `bytes`.
+pub fn decode_with_length(bytes: &[u8]) -> Result<EntryWithLength<'_>, Error> {
+    decode_entry(bytes, false)
+}
+
+/// Try and decode `bytes` as an [Entry], rejecting any encoding that isn't canonical.
+///
+/// [decode] is lenient, so different byte strings can decode to the same [Entry] (and so hash
+/// differently when used as a backlink.) This errors if:
+/// - the end of feed tag is not `0` or `1`
+/// - the author is a weak ed25519 key (see [is_weak_key])
+/// - any varu64 is not the shortest possible encoding
+/// - there are bytes after the signature
+/// - the [Entry] doesn't encode back to exactly `bytes`
+///
+/// Returned [Entry] references `bytes`.
+pub fn decode_strict(bytes: &[u8]) -> Result<Entry<&[u8], &[u8]>, Error> {
+    let (entry, length) = decode_entry(bytes, true)?;
+
+    ensure!(
+        length == bytes.len(),
+        DecodeTrailingBytes {
+            length: bytes.len() - length
+        }
+    );
+
+    let mut buff = [0u8; MAX_ENTRY_SIZE];
+    let encoded_size = entry
+        .encode(&mut buff)
+        .map_err(|_| Error::DecodeEncodingNotCanonical)?;
+
+    ensure!(&buff[..encoded_size] == bytes, DecodeEncodingNotCanonical);
+
+    Ok(entry)
+}
+
+fn decode_entry(bytes: &[u8], is_strict: bool) -> Result<EntryWithLength<'_>, Error> {
     ensure!(bytes.len() > 0, DecodeInputIsLengthZero);
 
+    ensure!(
+        !is_strict || bytes[0] <= 1,
+        DecodeEndOfFeedTagInvalid { tag: bytes[0] }
+    );
+
     // Decode is end of feed
     let is_end_of_feed = bytes[0] == 1;
 
@@ -26,17 +86,21 @@ pub fn decode<'a>(bytes: &'a [u8]) -> Result<Entry<&'a [u8], &'a [u8]>, Error> {
     let author = DalekPublicKey::from_bytes(&bytes[1..PUBLIC_KEY_LENGTH + 1])
         .map_err(|_| Error::DecodeAuthorError)?;
 
+    ensure!(!is_strict || !is_weak_key(&author), DecodeWeakAuthorKey);
+
     let remaining_bytes = &bytes[PUBLIC_KEY_LENGTH + 1..];
 
     // Decode the log id
-    let (log_id, remaining_bytes) = varu64_decode(remaining_bytes)
-        .map_err(|_| NoneError)
-        .context(DecodeLogIdError)?;
+    let (log_id, remaining_bytes) = decode_varu64(
+        remaining_bytes,
+        is_strict,
+        "log_id",
+        Error::DecodeLogIdError,
+    )?;
 
     // Decode the sequence number
-    let (seq_num, remaining_bytes) = varu64_decode(remaining_bytes)
-        .map_err(|_| NoneError)
-        .context(DecodeSeqError)?;
+    let (seq_num, remaining_bytes) =
+        decode_varu64(remaining_bytes, is_strict, "seq_num", Error::DecodeSeqError)?;
 
     ensure!(seq_num > 0, DecodeSeqIsZero { seq_num });
 
@@ -60,18 +124,22 @@ pub fn decode<'a>(bytes: &'a [u8]) -> Result<Entry<&'a [u8], &'a [u8]>, Error> {
     };
 
     // Decode the payload size
-    let (payload_size, remaining_bytes) = varu64_decode(remaining_bytes)
-        .map_err(|_| NoneError)
-        .context(DecodePayloadSizeError)?;
+    let (payload_size, remaining_bytes) = decode_varu64(
+        remaining_bytes,
+        is_strict,
+        "payload_size",
+        Error::DecodePayloadSizeError,
+    )?;
 
     // Decode the payload hash
     let (payload_hash, remaining_bytes) =
         YamfHash::<&[u8]>::decode(remaining_bytes).context(DecodePayloadHashError)?;
 
     // Decode the signature
-    let (sig, _) = Signature::<&[u8]>::decode(remaining_bytes).context(DecodeSigError)?;
+    let (sig, remaining_bytes) =
+        Signature::<&[u8]>::decode(remaining_bytes).context(DecodeSigError)?;
 
-    Ok(Entry {
+    let entry = Entry {
         log_id,
         is_end_of_feed,
         payload_hash,
@@ -81,5 +149,23 @@ pub fn decode<'a>(bytes: &'a [u8]) -> Result<Entry<&'a [u8], &'a [u8]>, Error> {
         backlink,
         lipmaa_link,
         sig: Some(sig),
+    };
+
+    Ok((entry, bytes.len() - remaining_bytes.len()))
+}
+
+// In strict mode a non canonical varu64 gets its own error, otherwise it's treated like any other
+// varu64 decoding error.
+fn decode_varu64<'a>(
+    bytes: &'a [u8],
+    is_strict: bool,
+    field: &'static str,
+    error: Error,
+) -> Result<(u64, &'a [u8]), Error> {
+    varu64_decode(bytes).map_err(|(err, _)| match err {
+        Varu64DecodeError::NonCanonical(value) if is_strict => {
+            Error::DecodeNonCanonicalVaru64 { field, value }
+        }
+        _ => error,
     })
 }
@@ -0,0 +1,111 @@
+use core::ops::Range;
+use snafu::ResultExt;
+use std::io::{ErrorKind, Read};
+
+use super::{decode, decode_with_length, Entry, ReadEntryBytes, ReadEntryDecode, ReadError};
+use crate::entry::MAX_ENTRY_SIZE;
+
+/// An encoded entry read by [EntryReader], along with where it was found in the stream.
+#[derive(Debug, Eq, PartialEq)]
+pub struct ReadEntry {
+    /// The encoded bytes of this entry.
+    pub bytes: Vec<u8>,
+    /// The range of the stream that `bytes` occupied.
+    pub range: Range<u64>,
+}
+
+impl ReadEntry {
+    /// Decode the [Entry] from `bytes`.
+    ///
+    /// [EntryReader] has already checked that `bytes` decodes, so this does not fail.
+    pub fn entry(&self) -> Entry<&[u8], &[u8]> {
+        decode(&self.bytes).expect("ReadEntry bytes were already decoded by EntryReader")
+    }
+}
+
+/// Reads back-to-back encoded entries from a [Read].
+///
+/// Never buffers more than [MAX_ENTRY_SIZE] bytes. Yields an `Err` and then stops if the stream
+/// can't be read or doesn't decode as an [Entry].
+#[derive(Debug)]
+pub struct EntryReader<R: Read> {
+    reader: R,
+    buffer: Vec<u8>,
+    consumed: u64,
+    is_eof: bool,
+    failed: bool,
+}
+
+impl<R: Read> EntryReader<R> {
+    /// Create an `EntryReader` that reads entries from `reader`.
+    pub fn new(reader: R) -> EntryReader<R> {
+        EntryReader {
+            reader,
+            buffer: Vec::with_capacity(MAX_ENTRY_SIZE),
+            consumed: 0,
+            is_eof: false,
+            failed: false,
+        }
+    }
+
+    /// The number of bytes of the stream that have been yielded as entries so far.
+    pub fn consumed(&self) -> u64 {
+        self.consumed
+    }
+
+    /// Consume the `EntryReader`, returning the inner reader.
+    ///
+    /// Any bytes that were buffered but not yielded are lost.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    // Read until the buffer holds at least one maximally sized entry, or the stream ends.
+    fn fill_buffer(&mut self) -> Result<(), std::io::Error> {
+        let mut chunk = [0u8; MAX_ENTRY_SIZE];
+
+        while !self.is_eof && self.buffer.len() < MAX_ENTRY_SIZE {
+            let wanted = MAX_ENTRY_SIZE - self.buffer.len();
+            match self.reader.read(&mut chunk[..wanted]) {
+                Ok(0) => self.is_eof = true,
+                Ok(n) => self.buffer.extend_from_slice(&chunk[..n]),
+                Err(err) if err.kind() == ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn read_entry(&mut self) -> Result<Option<ReadEntry>, ReadError> {
+        self.fill_buffer().context(ReadEntryBytes)?;
+
+        if self.buffer.is_empty() {
+            return Ok(None);
+        }
+
+        let (_, length) = decode_with_length(&self.buffer).context(ReadEntryDecode {
+            offset: self.consumed,
+        })?;
+
+        let bytes = self.buffer.drain(..length).collect();
+        let range = self.consumed..self.consumed + length as u64;
+        self.consumed += length as u64;
+
+        Ok(Some(ReadEntry { bytes, range }))
+    }
+}
+
+impl<R: Read> Iterator for EntryReader<R> {
+    type Item = Result<ReadEntry, ReadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+
+        let result = self.read_entry();
+        self.failed = result.is_err();
+        result.transpose()
+    }
+}
@@ -1,6 +1,6 @@
 use crate::signature::Error as SigError;
+use crate::yamf_hash::error::Error as YamfHashError;
 use snafu::Snafu;
-use yamf_hash::error::Error as YamfHashError;
 
 #[derive(Debug, Snafu)]
 #[snafu(visibility = "pub(super)")]
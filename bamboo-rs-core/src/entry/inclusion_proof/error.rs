@@ -0,0 +1,38 @@
+use crate::certificate_pool::Error as CertificatePoolError;
+use crate::entry::decode::Error as DecodeError;
+use crate::entry::verify::Error as VerifyError;
+use snafu::Snafu;
+
+#[derive(Debug, Snafu)]
+#[snafu(visibility = "pub(super)")]
+pub enum Error {
+    #[snafu(display("Invalid seq nums for an inclusion proof: {}", source))]
+    InvalidProofSeqNums { source: CertificatePoolError },
+    #[snafu(display(
+        "The entry with seq num {} is needed for the proof but was not found",
+        seq_num
+    ))]
+    ProofEntryNotFound { seq_num: u64 },
+    #[snafu(display("The inclusion proof is empty"))]
+    EmptyProof,
+    #[snafu(display("Failed to decode an entry in the proof: {}", source))]
+    DecodeProofEntry { source: DecodeError },
+    #[snafu(display("The head of the proof is not validly signed: {}", source))]
+    VerifyProofHead { source: VerifyError },
+    #[snafu(display(
+        "Entry {} in the proof does not link to the next entry {}",
+        seq_num,
+        next_seq_num
+    ))]
+    ProofEntriesNotLinked { seq_num: u64, next_seq_num: u64 },
+    #[snafu(display(
+        "Entry {} in the proof does not link to the next entry: {}",
+        seq_num,
+        source
+    ))]
+    VerifyProofLink { seq_num: u64, source: VerifyError },
+    #[snafu(display("The proof ends at seq num {} instead of seq num {}", actual, expected))]
+    ProofEndsAtWrongEntry { actual: u64, expected: u64 },
+}
+
+pub type Result<T, E = Error> = core::result::Result<T, E>;
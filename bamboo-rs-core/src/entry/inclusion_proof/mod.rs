@@ -0,0 +1,130 @@
+//! Prove that an entry is part of a log, given a signed entry that comes after it.
+//!
+//! A proof is the encoded entries along the shortest path of links (see
+//! [certificate_pool](mod@crate::certificate_pool)) from the head entry down to the
+//! entry being proved, encoded back-to-back so [decode_many] can read them.
+//! Only the head's signature needs to be trusted. Every other entry in the proof is authenticated
+//! by the hash that links to it.
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+#[cfg(feature = "alloc")]
+use core::borrow::Borrow;
+use snafu::{ensure, ResultExt};
+
+use super::decode::{decode, decode_many};
+use super::verify::{verify_backlink, verify_lipmaa_link};
+use super::{is_lipmaa_required, Entry};
+#[cfg(feature = "alloc")]
+use crate::certificate_pool::lipmaa_path;
+use crate::yamf_hash::YamfHash;
+use lipmaa_link::lipmaa;
+
+pub mod error;
+pub use error::*;
+
+/// An entry that [verify_inclusion_proof] proved is part of the log of a signed head entry.
+#[derive(Debug, Eq, PartialEq)]
+pub struct VerifiedInclusion<'a> {
+    head: Entry<&'a [u8], &'a [u8]>,
+    entry: Entry<&'a [u8], &'a [u8]>,
+    entry_bytes: &'a [u8],
+}
+
+impl<'a> VerifiedInclusion<'a> {
+    /// The signed head entry that the proof starts from.
+    pub fn head(&self) -> &Entry<&'a [u8], &'a [u8]> {
+        &self.head
+    }
+
+    /// The entry that is proved to be in the head's log.
+    pub fn entry(&self) -> &Entry<&'a [u8], &'a [u8]> {
+        &self.entry
+    }
+
+    /// The encoded bytes of [entry](VerifiedInclusion::entry).
+    pub fn entry_bytes(&self) -> &'a [u8] {
+        self.entry_bytes
+    }
+}
+
+/// Build a proof that entry `seq_num` is part of the log whose head is `head_seq_num`.
+///
+/// - `get_entry`: Returns the encoded entry with the seq num it's given, or `None` if it doesn't have it.
+///
+/// Only entries on the [lipmaa_path] from `head_seq_num` to `seq_num` are asked for.
+///
+/// The entries aren't checked, use [verify_inclusion_proof] for that.
+#[cfg(feature = "alloc")]
+pub fn inclusion_proof<F, B>(head_seq_num: u64, seq_num: u64, mut get_entry: F) -> Result<Vec<u8>>
+where
+    F: FnMut(u64) -> Option<B>,
+    B: Borrow<[u8]>,
+{
+    lipmaa_path(head_seq_num, seq_num)
+        .context(InvalidProofSeqNums)?
+        .try_fold(Vec::new(), |mut proof, seq_num| {
+            let entry = get_entry(seq_num).ok_or(Error::ProofEntryNotFound { seq_num })?;
+            proof.extend_from_slice(entry.borrow());
+            Ok(proof)
+        })
+}
+
+/// Verify a proof made by [inclusion_proof] that the entry with `seq_num` is part of a log.
+///
+/// Checks the signature of the head entry at the start of the proof, then checks each entry
+/// links to the next one by its lipmaa link or backlink, down to the entry with `seq_num`.
+///
+/// Returns a [VerifiedInclusion] holding the head and the proved entry. It's up to the caller to
+/// check the head is the one they expected, eg. by its author, log_id and seq num.
+pub fn verify_inclusion_proof<'a>(proof: &'a [u8], seq_num: u64) -> Result<VerifiedInclusion<'a>> {
+    let mut entries = decode_many(proof);
+
+    let head = entries
+        .next()
+        .ok_or(Error::EmptyProof)?
+        .context(DecodeProofEntry)?;
+    head.entry.verify_signature().context(VerifyProofHead)?;
+
+    let head_bytes = head.bytes;
+    let mut current = head;
+
+    for next in entries {
+        let next = next.context(DecodeProofEntry)?;
+        let linked_seq_num = current.entry.seq_num;
+
+        if next.entry.seq_num == lipmaa(linked_seq_num) && is_lipmaa_required(linked_seq_num) {
+            let algorithm = current.entry.lipmaa_link.as_ref().map(YamfHash::algorithm);
+            let link = (next.bytes, algorithm.unwrap_or_default().hash(next.bytes));
+            verify_lipmaa_link(&current.entry, Some(link))
+        } else if next.entry.seq_num + 1 == linked_seq_num {
+            let algorithm = current.entry.backlink.as_ref().map(YamfHash::algorithm);
+            let link = (next.bytes, algorithm.unwrap_or_default().hash(next.bytes));
+            verify_backlink(&current.entry, Some(link))
+        } else {
+            return Err(Error::ProofEntriesNotLinked {
+                seq_num: linked_seq_num,
+                next_seq_num: next.entry.seq_num,
+            });
+        }
+        .context(VerifyProofLink {
+            seq_num: linked_seq_num,
+        })?;
+
+        current = next;
+    }
+
+    ensure!(
+        current.entry.seq_num == seq_num,
+        ProofEndsAtWrongEntry {
+            actual: current.entry.seq_num,
+            expected: seq_num
+        }
+    );
+
+    Ok(VerifiedInclusion {
+        // Decoded again so the head and entry can both be returned when they are the same.
+        head: decode(head_bytes).context(DecodeProofEntry)?,
+        entry: current.entry,
+        entry_bytes: current.bytes,
+    })
+}
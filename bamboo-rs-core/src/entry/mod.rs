@@ -1,19 +1,43 @@
 use arrayvec::ArrayVec;
 use core::borrow::Borrow;
 use core::convert::TryFrom;
+use core::fmt;
 use lipmaa_link::lipmaa;
 
 pub mod decode;
 pub mod encode;
+pub mod inclusion_proof;
+pub mod owned;
 pub mod publish;
 pub mod verify;
 
-pub use decode::decode;
-pub use publish::publish;
-pub use verify::verify;
+pub use decode::{decode, decode_many, decode_strict};
+pub use inclusion_proof::{verify_inclusion_proof, VerifiedInclusion};
+pub use owned::{EntryBuilder, OwnedEntry};
+pub use publish::{
+    attach_signature, publish, publish_unsigned, publish_unsigned_with_hash_algorithm,
+    publish_with_hash_algorithm, publish_with_links, publish_with_links_and_hash_algorithm,
+    publish_with_payload_hash, EntrySigner,
+};
+pub use verify::{
+    verify, verify_entry, verify_strict, verify_with_payload_hash, verify_with_policy,
+    VerifiedEntry, VerifyPolicy,
+};
 
 #[cfg(feature = "std")]
-pub use verify::verify_batch;
+pub use decode::EntryReader;
+#[cfg(feature = "alloc")]
+pub use inclusion_proof::inclusion_proof;
+#[cfg(feature = "std")]
+pub use publish::publish_from_reader;
+#[cfg(feature = "alloc")]
+pub use publish::publish_many;
+#[cfg(feature = "std")]
+pub use verify::{
+    verify_batch, verify_batch_multi_feed, verify_batch_report, verify_batch_report_strict,
+    verify_batch_report_with_policy, verify_batch_strict, verify_batch_with_policy,
+    verify_from_reader,
+};
 
 #[cfg(feature = "std")]
 use crate::util::hex_serde::*;
@@ -24,6 +48,7 @@ use serde::{Deserialize, Serialize};
 use ed25519_dalek::PublicKey as DalekPublicKey;
 
 use super::signature::{Signature, MAX_SIGNATURE_SIZE};
+use super::util::write_hex;
 use super::yamf_hash::{YamfHash, MAX_YAMF_HASH_SIZE};
 
 pub use ed25519_dalek::PUBLIC_KEY_LENGTH;
@@ -80,6 +105,19 @@ impl<'a> TryFrom<&'a [u8]> for Entry<&'a [u8], &'a [u8]> {
     }
 }
 
+/// Formats the encoded entry as lower case hex. Parse it back with [OwnedEntry]'s `FromStr`.
+impl<H, S> fmt::Display for Entry<H, S>
+where
+    H: Borrow<[u8]>,
+    S: Borrow<[u8]>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut out = [0u8; MAX_ENTRY_SIZE];
+        let size = self.encode(&mut out).map_err(|_| fmt::Error)?;
+        write_hex(f, &out[..size])
+    }
+}
+
 impl<'a, H, S> TryFrom<Entry<H, S>> for ArrayVec<[u8; 512]>
 where
     H: Borrow<[u8]>,
@@ -112,31 +150,15 @@ where
         None => None,
     };
 
-    let payload_hash = match entry.payload_hash {
-        YamfHash::Blake2b(ref s) => {
-            let mut vec = ArrayVec::<[u8; 64]>::new();
-            vec.try_extend_from_slice(&s.borrow()[..]).unwrap();
-            YamfHash::Blake2b(vec)
-        }
+    let into_owned_hash = |hash: &YamfHash<H>| {
+        let mut vec = ArrayVec::<[u8; 64]>::new();
+        vec.try_extend_from_slice(hash.as_bytes()).unwrap();
+        hash.algorithm().with_bytes(vec)
     };
 
-    let backlink = match entry.backlink {
-        Some(YamfHash::Blake2b(ref s)) => {
-            let mut vec = ArrayVec::<[u8; 64]>::new();
-            vec.try_extend_from_slice(&s.borrow()[..]).unwrap();
-            Some(YamfHash::Blake2b(vec))
-        }
-        None => None,
-    };
-
-    let lipmaa_link = match entry.lipmaa_link {
-        Some(YamfHash::Blake2b(ref s)) => {
-            let mut vec = ArrayVec::<[u8; 64]>::new();
-            vec.try_extend_from_slice(&s.borrow()[..]).unwrap();
-            Some(YamfHash::Blake2b(vec))
-        }
-        None => None,
-    };
+    let payload_hash = into_owned_hash(&entry.payload_hash);
+    let backlink = entry.backlink.as_ref().map(into_owned_hash);
+    let lipmaa_link = entry.lipmaa_link.as_ref().map(into_owned_hash);
 
     Entry {
         is_end_of_feed: entry.is_end_of_feed,
@@ -0,0 +1,38 @@
+use crate::entry::decode::Error as EntryDecodeError;
+use snafu::Snafu;
+
+#[derive(Debug, Snafu)]
+#[snafu(visibility = "pub(super)")]
+pub enum Error {
+    #[snafu(display("Entry must have an author"))]
+    BuildWithoutAuthor,
+    #[snafu(display("Entry must have a payload hash"))]
+    BuildWithoutPayloadHash,
+    #[snafu(display("Entry seq_num was 0 which is not valid"))]
+    BuildSeqIsZero,
+    #[snafu(display("The first entry of a log can't have a backlink or lipmaa link"))]
+    BuildFirstEntryHasLinks,
+    #[snafu(display("Entry with seq_num {} needs a backlink", seq_num))]
+    BuildWithoutBacklink { seq_num: u64 },
+    #[snafu(display("Entry with seq_num {} needs a lipmaa link", seq_num))]
+    BuildWithoutLipmaaLink { seq_num: u64 },
+    #[snafu(display(
+        "Entry with seq_num {} must not have a lipmaa link because it is the same as the backlink",
+        seq_num
+    ))]
+    BuildWithUnneededLipmaaLink { seq_num: u64 },
+    #[snafu(display("Hash must be {} bytes but was {} bytes", expected, length))]
+    InvalidHashLength { length: usize, expected: usize },
+    #[snafu(display("Signature must be 64 bytes but was {} bytes", length))]
+    InvalidSignatureLength { length: usize },
+    #[snafu(display("Entry string is longer than the hex encoding of the largest entry"))]
+    EntryStringTooLong,
+    #[snafu(display("Entry string is not valid hex"))]
+    DecodeEntryHex,
+    #[snafu(display("Could not decode entry string: {}", source))]
+    DecodeEntryString { source: EntryDecodeError },
+    #[snafu(display("Entry string has {} bytes after the end of the entry", length))]
+    EntryStringHasTrailingBytes { length: usize },
+}
+
+pub type Result<T, E = Error> = core::result::Result<T, E>;
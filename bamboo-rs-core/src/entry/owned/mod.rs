@@ -0,0 +1,331 @@
+use arrayvec::ArrayVec;
+use core::borrow::Borrow;
+use core::convert::TryFrom;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::str::FromStr;
+use snafu::{ensure, NoneError, ResultExt};
+
+#[cfg(feature = "std")]
+use crate::util::hex_serde::serialize_pub_key;
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+
+use ed25519_dalek::PublicKey as DalekPublicKey;
+
+use super::decode::decode_with_length;
+use super::{is_lipmaa_required, Entry, MAX_ENTRY_SIZE};
+use crate::signature::{Signature, ED25519_SIGNATURE_SIZE};
+use crate::yamf_hash::{new_blake2b, YamfHash, BLAKE2B_HASH_SIZE};
+
+pub mod error;
+pub use error::*;
+
+pub type OwnedYamfHash = YamfHash<ArrayVec<[u8; BLAKE2B_HASH_SIZE]>>;
+pub type OwnedSignature = Signature<ArrayVec<[u8; ED25519_SIGNATURE_SIZE]>>;
+
+/// An [Entry] that owns its hashes and signature.
+///
+/// Unlike an [Entry] returned by [decode](super::decode()), it doesn't borrow the bytes it was
+/// decoded from, so it can be cloned, kept in maps and caches, and round tripped through serde.
+/// Construct one with an [EntryBuilder] or by converting from an [Entry].
+#[cfg_attr(feature = "std", derive(Deserialize, Serialize))]
+#[cfg_attr(
+    feature = "std",
+    serde(rename_all = "camelCase", try_from = "Entry<Vec<u8>, Vec<u8>>")
+)]
+#[derive(Debug, Eq, PartialEq)]
+pub struct OwnedEntry {
+    pub log_id: u64,
+    pub is_end_of_feed: bool,
+    pub payload_hash: OwnedYamfHash,
+    pub payload_size: u64,
+    #[cfg_attr(feature = "std", serde(serialize_with = "serialize_pub_key"))]
+    pub author: DalekPublicKey,
+    pub seq_num: u64,
+    pub backlink: Option<OwnedYamfHash>,
+    pub lipmaa_link: Option<OwnedYamfHash>,
+    pub sig: Option<OwnedSignature>,
+}
+
+impl OwnedEntry {
+    /// Borrow this as an [Entry], eg. to encode or verify it.
+    pub fn as_entry(&self) -> Entry<&[u8], &[u8]> {
+        Entry::from(self)
+    }
+}
+
+impl Clone for OwnedEntry {
+    fn clone(&self) -> Self {
+        OwnedEntry {
+            log_id: self.log_id,
+            is_end_of_feed: self.is_end_of_feed,
+            payload_hash: self.payload_hash.clone(),
+            payload_size: self.payload_size,
+            author: self.author,
+            seq_num: self.seq_num,
+            backlink: self.backlink.clone(),
+            lipmaa_link: self.lipmaa_link.clone(),
+            sig: self.sig.as_ref().map(|sig| Signature(sig.0.clone())),
+        }
+    }
+}
+
+impl Hash for OwnedEntry {
+    fn hash<HS: Hasher>(&self, state: &mut HS) {
+        self.log_id.hash(state);
+        self.is_end_of_feed.hash(state);
+        self.payload_hash.hash(state);
+        self.payload_size.hash(state);
+        self.author.as_bytes().hash(state);
+        self.seq_num.hash(state);
+        self.backlink.hash(state);
+        self.lipmaa_link.hash(state);
+        self.sig.as_ref().map(|sig| &sig.0[..]).hash(state);
+    }
+}
+
+impl fmt::Display for OwnedEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.as_entry().fmt(f)
+    }
+}
+
+/// Parses the hex encoded entry written by `Display`.
+impl FromStr for OwnedEntry {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<OwnedEntry, Error> {
+        let mut bytes = [0u8; MAX_ENTRY_SIZE];
+        let size = s.len() / 2;
+
+        ensure!(size <= MAX_ENTRY_SIZE, EntryStringTooLong);
+        hex::decode_to_slice(s, &mut bytes[..size])
+            .map_err(|_| NoneError)
+            .context(DecodeEntryHex)?;
+
+        let (entry, length) = decode_with_length(&bytes[..size]).context(DecodeEntryString)?;
+        ensure!(
+            length == size,
+            EntryStringHasTrailingBytes {
+                length: size - length
+            }
+        );
+
+        OwnedEntry::try_from(entry)
+    }
+}
+
+impl<'a> From<&'a OwnedEntry> for Entry<&'a [u8], &'a [u8]> {
+    fn from(entry: &'a OwnedEntry) -> Entry<&'a [u8], &'a [u8]> {
+        Entry {
+            log_id: entry.log_id,
+            is_end_of_feed: entry.is_end_of_feed,
+            payload_hash: (&entry.payload_hash).into(),
+            payload_size: entry.payload_size,
+            author: entry.author,
+            seq_num: entry.seq_num,
+            backlink: entry.backlink.as_ref().map(|link| link.into()),
+            lipmaa_link: entry.lipmaa_link.as_ref().map(|link| link.into()),
+            sig: entry.sig.as_ref().map(|sig| Signature(&sig.0[..])),
+        }
+    }
+}
+
+impl<'a, H, S> TryFrom<&'a Entry<H, S>> for OwnedEntry
+where
+    H: Borrow<[u8]>,
+    S: Borrow<[u8]>,
+{
+    type Error = Error;
+
+    fn try_from(entry: &'a Entry<H, S>) -> Result<OwnedEntry, Self::Error> {
+        let mut builder = EntryBuilder::new()
+            .log_id(entry.log_id)
+            .is_end_of_feed(entry.is_end_of_feed)
+            .payload_hash(own_hash(&entry.payload_hash)?, entry.payload_size)
+            .author(entry.author)
+            .seq_num(entry.seq_num);
+
+        if let Some(ref backlink) = entry.backlink {
+            builder = builder.backlink(own_hash(backlink)?);
+        }
+        if let Some(ref lipmaa_link) = entry.lipmaa_link {
+            builder = builder.lipmaa_link(own_hash(lipmaa_link)?);
+        }
+        if let Some(ref sig) = entry.sig {
+            builder = builder.sig(own_signature(sig)?);
+        }
+
+        builder.build()
+    }
+}
+
+impl<H, S> TryFrom<Entry<H, S>> for OwnedEntry
+where
+    H: Borrow<[u8]>,
+    S: Borrow<[u8]>,
+{
+    type Error = Error;
+
+    fn try_from(entry: Entry<H, S>) -> Result<OwnedEntry, Self::Error> {
+        OwnedEntry::try_from(&entry)
+    }
+}
+
+/// Builds an [OwnedEntry], checking that the fields make a valid entry.
+///
+/// The author and payload hash must be set. `seq_num` defaults to `1`. The backlink and lipmaa
+/// link must match what the bamboo spec requires for `seq_num`.
+#[derive(Debug)]
+pub struct EntryBuilder {
+    log_id: u64,
+    is_end_of_feed: bool,
+    payload_hash: Option<(OwnedYamfHash, u64)>,
+    author: Option<DalekPublicKey>,
+    seq_num: u64,
+    backlink: Option<OwnedYamfHash>,
+    lipmaa_link: Option<OwnedYamfHash>,
+    sig: Option<OwnedSignature>,
+}
+
+impl Default for EntryBuilder {
+    fn default() -> Self {
+        EntryBuilder::new()
+    }
+}
+
+impl EntryBuilder {
+    pub fn new() -> EntryBuilder {
+        EntryBuilder {
+            log_id: 0,
+            is_end_of_feed: false,
+            payload_hash: None,
+            author: None,
+            seq_num: 1,
+            backlink: None,
+            lipmaa_link: None,
+            sig: None,
+        }
+    }
+
+    pub fn log_id(mut self, log_id: u64) -> Self {
+        self.log_id = log_id;
+        self
+    }
+
+    pub fn is_end_of_feed(mut self, is_end_of_feed: bool) -> Self {
+        self.is_end_of_feed = is_end_of_feed;
+        self
+    }
+
+    /// Set the payload hash and size by hashing `payload`.
+    pub fn payload(self, payload: &[u8]) -> Self {
+        self.payload_hash(new_blake2b(payload), payload.len() as u64)
+    }
+
+    /// Set the payload hash and size, for when the payload has already been hashed.
+    pub fn payload_hash(mut self, payload_hash: OwnedYamfHash, payload_size: u64) -> Self {
+        self.payload_hash = Some((payload_hash, payload_size));
+        self
+    }
+
+    pub fn author(mut self, author: DalekPublicKey) -> Self {
+        self.author = Some(author);
+        self
+    }
+
+    pub fn seq_num(mut self, seq_num: u64) -> Self {
+        self.seq_num = seq_num;
+        self
+    }
+
+    pub fn backlink(mut self, backlink: OwnedYamfHash) -> Self {
+        self.backlink = Some(backlink);
+        self
+    }
+
+    pub fn lipmaa_link(mut self, lipmaa_link: OwnedYamfHash) -> Self {
+        self.lipmaa_link = Some(lipmaa_link);
+        self
+    }
+
+    pub fn sig(mut self, sig: OwnedSignature) -> Self {
+        self.sig = Some(sig);
+        self
+    }
+
+    pub fn build(self) -> Result<OwnedEntry, Error> {
+        let author = self.author.ok_or(Error::BuildWithoutAuthor)?;
+        let (payload_hash, payload_size) =
+            self.payload_hash.ok_or(Error::BuildWithoutPayloadHash)?;
+        let seq_num = self.seq_num;
+
+        ensure!(seq_num > 0, BuildSeqIsZero);
+
+        check_hash_length(&payload_hash)?;
+        if let Some(ref sig) = self.sig {
+            ensure!(
+                sig.len() == ED25519_SIGNATURE_SIZE,
+                InvalidSignatureLength { length: sig.len() }
+            );
+        }
+
+        match (seq_num, &self.backlink, &self.lipmaa_link) {
+            (1, None, None) => Ok(()),
+            (1, _, _) => Err(Error::BuildFirstEntryHasLinks),
+            (_, None, _) => Err(Error::BuildWithoutBacklink { seq_num }),
+            (_, Some(_), None) if is_lipmaa_required(seq_num) => {
+                Err(Error::BuildWithoutLipmaaLink { seq_num })
+            }
+            (_, Some(_), Some(_)) if !is_lipmaa_required(seq_num) => {
+                Err(Error::BuildWithUnneededLipmaaLink { seq_num })
+            }
+            (_, Some(backlink), lipmaa_link) => {
+                check_hash_length(backlink)?;
+                lipmaa_link.as_ref().map(check_hash_length).transpose()?;
+                Ok(())
+            }
+        }?;
+
+        Ok(OwnedEntry {
+            log_id: self.log_id,
+            is_end_of_feed: self.is_end_of_feed,
+            payload_hash,
+            payload_size,
+            author,
+            seq_num,
+            backlink: self.backlink,
+            lipmaa_link: self.lipmaa_link,
+            sig: self.sig,
+        })
+    }
+}
+
+fn check_hash_length(hash: &OwnedYamfHash) -> Result<(), Error> {
+    let length = hash.as_bytes().len();
+    let expected = hash.algorithm().hash_size();
+    ensure!(length == expected, InvalidHashLength { length, expected });
+    Ok(())
+}
+
+fn own_hash<H: Borrow<[u8]>>(hash: &YamfHash<H>) -> Result<OwnedYamfHash, Error> {
+    let bytes = hash.as_bytes();
+    let mut vec = ArrayVec::new();
+    vec.try_extend_from_slice(bytes)
+        .map_err(|_| Error::InvalidHashLength {
+            length: bytes.len(),
+            expected: hash.algorithm().hash_size(),
+        })?;
+    Ok(hash.algorithm().with_bytes(vec))
+}
+
+fn own_signature<S: Borrow<[u8]>>(sig: &Signature<S>) -> Result<OwnedSignature, Error> {
+    let bytes = sig.0.borrow();
+    let mut vec = ArrayVec::new();
+    vec.try_extend_from_slice(bytes)
+        .map_err(|_| Error::InvalidSignatureLength {
+            length: bytes.len(),
+        })?;
+    Ok(Signature(vec))
+}
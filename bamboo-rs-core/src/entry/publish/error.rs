@@ -1,5 +1,7 @@
 use crate::entry::decode::Error as EntryDecodeError;
 use crate::entry::encode::Error as EntryEncodeError;
+use crate::entry::verify::Error as EntryVerifyError;
+use crate::yamf_hash::HashAlgorithm;
 use snafu::Snafu;
 
 #[derive(Debug, Snafu)]
@@ -36,6 +38,45 @@ pub enum Error {
         source: EntryEncodeError,
         buffer_size: usize,
     },
+    #[snafu(display("Signature must be 64 bytes but was {} bytes", length))]
+    PublishWithInvalidSignatureLength { length: usize },
+    #[snafu(display(
+        "The out buffer is too small to attach the signature. Buffer len: {}, required: {}",
+        buffer_size,
+        required_size
+    ))]
+    PublishOutBufferTooSmall {
+        buffer_size: usize,
+        required_size: usize,
+    },
+    #[snafu(display("Failed to decode the signed entry: {}", source))]
+    DecodeSignedEntry { source: EntryDecodeError },
+    #[snafu(display("The signature is not valid for the entry and its author: {}", source))]
+    PublishWithInvalidSignature { source: EntryVerifyError },
+    #[snafu(display("The signer failed to sign the entry"))]
+    PublishSignFailed,
+    #[snafu(display(
+        "Attempting to publish with {:?} to a log that uses {:?}",
+        expected,
+        actual
+    ))]
+    PublishWithDifferentHashAlgorithm {
+        expected: HashAlgorithm,
+        actual: HashAlgorithm,
+    },
+    #[cfg_attr(
+        feature = "std",
+        snafu(display("Failed to read the payload: {}", source))
+    )]
+    #[cfg_attr(not(feature = "std"), snafu(display("Failed to read the payload")))]
+    PublishReadPayload {
+        #[cfg(feature = "std")]
+        source: std::io::Error,
+    },
+    #[snafu(display("Payload hash must be {} bytes but was {} bytes", expected, length))]
+    PublishWithInvalidPayloadHashLength { length: usize, expected: usize },
+    #[snafu(display("Failed to decode an ancestor entry, encoding error: {}", source))]
+    DecodeAncestorEntry { source: EntryDecodeError },
 }
 
 pub type Result<T, E = Error> = core::result::Result<T, E>;
@@ -0,0 +1,181 @@
+use alloc::vec::Vec;
+use lipmaa_link::lipmaa;
+use snafu::{ensure, ResultExt};
+
+use super::{check_hash_algorithm, sign_entry, unsigned_entry_with_links, EntrySigner, Error};
+use super::{DecodeAncestorEntry, PublishAfterEndOfFeed, PublishWithoutBacklinkEntry};
+use super::{PublishKeypairDidNotMatchBacklinkPublicKey, PublishWithIncorrectBacklinkLogId};
+use crate::certificate_pool::lipmaa_path;
+use crate::entry::decode::decode;
+use crate::entry::owned::OwnedYamfHash;
+use crate::entry::MAX_ENTRY_SIZE;
+use crate::yamf_hash::HashAlgorithm;
+
+/// Iterator of encoded entries published by [publish_many].
+///
+/// Yields an `Err` and then stops if an entry can't be published.
+pub struct PublishMany<'a, K: ?Sized, I> {
+    key_pair: &'a K,
+    hash_algorithm: HashAlgorithm,
+    log_id: u64,
+    payloads: I,
+    previous_seq_num: Option<u64>,
+    // Hashes of the entries that later entries can link to, by seq num.
+    ancestors: Vec<(u64, OwnedYamfHash)>,
+    failed: bool,
+}
+
+/// Publish a sequence of payloads as consecutive entries of a log.
+///
+/// - `key_pair`: The [EntrySigner] used to sign the entries, usually an ed25519 [Keypair](ed25519_dalek::Keypair).
+/// - `hash_algorithm`: The [HashAlgorithm] used for the payloads and links.
+/// - `log_id`: The integer that distinguishes different logs by the same author.
+/// - `previous_seq_num`: The seq num of the last entry already in the log. `None` for a new log.
+/// - `ancestors`: The encoded entries on the [lipmaa_path] from `previous_seq_num` to `1`. Any other entries are ignored.
+/// - `payloads`: The payloads to publish, in order.
+///
+/// Only the hashes of the entries later entries can link to are kept, which is a few dozen even
+/// for very long logs. The ancestors are checked like [publish](super::publish) checks the
+/// lipmaa and backlink entries.
+///
+/// Returns an iterator that publishes the next payload each time it's advanced, yielding the
+/// encoded entry.
+pub fn publish_many<'a, K, I, B>(
+    key_pair: &'a K,
+    hash_algorithm: HashAlgorithm,
+    log_id: u64,
+    previous_seq_num: Option<u64>,
+    ancestors: impl IntoIterator<Item = B>,
+    payloads: I,
+) -> Result<PublishMany<'a, K, I::IntoIter>, Error>
+where
+    K: EntrySigner + ?Sized,
+    I: IntoIterator,
+    I::Item: AsRef<[u8]>,
+    B: AsRef<[u8]>,
+{
+    let mut publish_many = PublishMany {
+        key_pair,
+        hash_algorithm,
+        log_id,
+        payloads: payloads.into_iter(),
+        previous_seq_num,
+        ancestors: Vec::new(),
+        failed: false,
+    };
+
+    let previous_seq_num = match previous_seq_num.filter(|seq_num| *seq_num > 0) {
+        Some(previous_seq_num) => previous_seq_num,
+        None => return Ok(publish_many),
+    };
+
+    let author = key_pair.public_key();
+    let path = path_to_first_entry(previous_seq_num);
+
+    for bytes in ancestors {
+        let bytes = bytes.as_ref();
+        let entry = decode(bytes).context(DecodeAncestorEntry)?;
+
+        if !path.contains(&entry.seq_num) {
+            continue;
+        }
+
+        ensure!(log_id == entry.log_id, PublishWithIncorrectBacklinkLogId);
+        ensure!(
+            author == entry.author,
+            PublishKeypairDidNotMatchBacklinkPublicKey
+        );
+        check_hash_algorithm(hash_algorithm, entry.payload_hash.algorithm())?;
+
+        if entry.seq_num == previous_seq_num {
+            ensure!(!entry.is_end_of_feed, PublishAfterEndOfFeed);
+        }
+
+        publish_many
+            .ancestors
+            .push((entry.seq_num, hash_algorithm.hash(bytes)));
+    }
+
+    ensure!(
+        publish_many
+            .ancestors
+            .iter()
+            .any(|(seq_num, _)| *seq_num == previous_seq_num),
+        PublishWithoutBacklinkEntry
+    );
+
+    Ok(publish_many)
+}
+
+impl<'a, K: EntrySigner + ?Sized, I> PublishMany<'a, K, I>
+where
+    I: Iterator,
+    I::Item: AsRef<[u8]>,
+{
+    /// The seq num of the last entry published, or the one passed to [publish_many] if none have
+    /// been yet.
+    pub fn previous_seq_num(&self) -> Option<u64> {
+        self.previous_seq_num
+    }
+
+    fn publish_next(&mut self, payload: &[u8]) -> Result<Vec<u8>, Error> {
+        let seq_num = self.previous_seq_num.unwrap_or(0) + 1;
+        let ancestor = |seq_num| {
+            self.ancestors
+                .iter()
+                .find(|(ancestor, _)| *ancestor == seq_num)
+                .map(|(_, hash)| hash.clone())
+        };
+
+        let entry = unsigned_entry_with_links(
+            self.key_pair.public_key(),
+            self.log_id,
+            self.hash_algorithm.hash(payload),
+            payload.len() as u64,
+            false,
+            self.previous_seq_num,
+            ancestor(lipmaa(seq_num)),
+            ancestor(seq_num - 1),
+            false,
+        )?;
+
+        let mut out = [0u8; MAX_ENTRY_SIZE];
+        let size = sign_entry(&mut out, self.key_pair, entry)?;
+        let bytes = out[..size].to_vec();
+
+        // Only the entries on the path back to the first entry can be linked to by later
+        // entries, so forget the rest.
+        let path = path_to_first_entry(seq_num);
+        self.ancestors
+            .retain(|(ancestor, _)| path.contains(ancestor));
+        self.ancestors
+            .push((seq_num, self.hash_algorithm.hash(&bytes)));
+        self.previous_seq_num = Some(seq_num);
+
+        Ok(bytes)
+    }
+}
+
+impl<'a, K: EntrySigner + ?Sized, I> Iterator for PublishMany<'a, K, I>
+where
+    I: Iterator,
+    I::Item: AsRef<[u8]>,
+{
+    type Item = Result<Vec<u8>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+
+        let payload = self.payloads.next()?;
+        let result = self.publish_next(payload.as_ref());
+        self.failed = result.is_err();
+
+        Some(result)
+    }
+}
+
+fn path_to_first_entry(seq_num: u64) -> Vec<u64> {
+    lipmaa_path(seq_num, 1).into_iter().flatten().collect()
+}
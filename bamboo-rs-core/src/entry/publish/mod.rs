@@ -1,19 +1,46 @@
 pub use crate::BLAKE2B_HASH_SIZE;
 
+use arrayvec::ArrayVec;
+use core::borrow::Borrow;
+
 use super::decode::decode;
 use super::{is_lipmaa_required, Entry};
-use crate::signature::Signature;
-use crate::yamf_hash::new_blake2b;
-use ed25519_dalek::{Keypair, Signer};
-use snafu::{ensure, ResultExt};
+use crate::signature::{Signature, ED25519_SIGNATURE_SIZE};
+#[cfg(feature = "std")]
+use crate::yamf_hash::YamfHasher;
+use crate::yamf_hash::{HashAlgorithm, YamfHash};
+use ed25519_dalek::{Keypair, PublicKey, Signature as DalekSignature, Signer};
+use snafu::{ensure, NoneError, ResultExt};
+#[cfg(feature = "std")]
+use std::io::{self, Read};
 
 pub mod error;
 pub use error::*;
 
+#[cfg(feature = "alloc")]
+pub mod many;
+#[cfg(feature = "alloc")]
+pub use many::{publish_many, PublishMany};
+
+/// Something that can sign entries for an author.
+///
+/// Implemented for [Keypair]. Implement it for anything else that holds (or can reach) the
+/// author's secret key, like a keystore or hardware signer.
+pub trait EntrySigner: Signer<DalekSignature> {
+    /// The public key of the author that this signs for.
+    fn public_key(&self) -> PublicKey;
+}
+
+impl EntrySigner for Keypair {
+    fn public_key(&self) -> PublicKey {
+        self.public
+    }
+}
+
 /// Publish a new entry into the `out` buffer.
 ///
 /// - `out`: A buffer to encode the entry into. Must be >= MAX_ENTRY_SIZE.
-/// - `key_pair`: The ed25519 cryptographic key pair used to sign the entry.
+/// - `key_pair`: The [EntrySigner] used to sign the entry, usually an ed25519 [Keypair].
 /// - `log_id`: The integer that distinguishes different logs by the same author.
 /// - `payload`: The payload of the entry. Note that only the hash of the payload becomes part of the entry. It's up to the caller to store the actual payload somewhere.
 /// - `is_end_of_feed`: Is this entry the final entry for this `log_id`?
@@ -22,9 +49,9 @@ pub use error::*;
 /// - `backlink_bytes`: The encoded backlkink. `None` if this is the first entry.
 ///
 /// Returns a `Result` of the size of the entry encoded into `out`.
-pub fn publish(
+pub fn publish<K: EntrySigner + ?Sized>(
     out: &mut [u8],
-    key_pair: &Keypair,
+    key_pair: &K,
     log_id: u64,
     payload: &[u8],
     is_end_of_feed: bool,
@@ -32,35 +59,377 @@ pub fn publish(
     lipmaa_entry_bytes: Option<&[u8]>,
     backlink_bytes: Option<&[u8]>,
 ) -> Result<usize, Error> {
-    let author = key_pair.public;
+    publish_with_hash_algorithm(
+        out,
+        key_pair,
+        HashAlgorithm::Blake2b,
+        log_id,
+        payload,
+        is_end_of_feed,
+        previous_seq_num,
+        lipmaa_entry_bytes,
+        backlink_bytes,
+    )
+}
 
-    // calc the payload hash
-    let payload_hash = new_blake2b(payload);
-    let payload_size = payload.len() as u64;
+/// Publish a new entry into the `out` buffer, hashing the payload and links with
+/// `hash_algorithm`.
+///
+/// Takes the same arguments as [publish], which always uses [HashAlgorithm::Blake2b]. Every entry
+/// in a log must use the same algorithm, so publishing with a different algorithm to the
+/// previous entries is an error.
+///
+/// Returns a `Result` of the size of the entry encoded into `out`.
+#[allow(clippy::too_many_arguments)]
+pub fn publish_with_hash_algorithm<K: EntrySigner + ?Sized>(
+    out: &mut [u8],
+    key_pair: &K,
+    hash_algorithm: HashAlgorithm,
+    log_id: u64,
+    payload: &[u8],
+    is_end_of_feed: bool,
+    previous_seq_num: Option<u64>,
+    lipmaa_entry_bytes: Option<&[u8]>,
+    backlink_bytes: Option<&[u8]>,
+) -> Result<usize, Error> {
+    let entry = unsigned_entry(
+        key_pair.public_key(),
+        log_id,
+        hash_algorithm.hash(payload),
+        payload.len() as u64,
+        is_end_of_feed,
+        previous_seq_num,
+        lipmaa_entry_bytes,
+        backlink_bytes,
+    )?;
 
-    let seq_num = previous_seq_num.unwrap_or(0) + 1;
+    sign_entry(out, key_pair, entry)
+}
 
-    let mut entry: Entry<_, &[u8]> = Entry {
+/// Publish a new entry into the `out` buffer using the hash and size of a payload that was
+/// hashed somewhere else, eg. by a content store that already knows it.
+///
+/// Takes the same arguments as [publish], except `payload` is replaced by:
+/// - `payload_hash`: The hash of the payload. Its algorithm is used for the links too.
+/// - `payload_size`: The size of the payload in bytes.
+///
+/// The hash isn't checked against the payload, so it's up to the caller to make sure it's right.
+///
+/// Returns a `Result` of the size of the entry encoded into `out`.
+#[allow(clippy::too_many_arguments)]
+pub fn publish_with_payload_hash<K: EntrySigner + ?Sized, H: Borrow<[u8]>>(
+    out: &mut [u8],
+    key_pair: &K,
+    log_id: u64,
+    payload_hash: &YamfHash<H>,
+    payload_size: u64,
+    is_end_of_feed: bool,
+    previous_seq_num: Option<u64>,
+    lipmaa_entry_bytes: Option<&[u8]>,
+    backlink_bytes: Option<&[u8]>,
+) -> Result<usize, Error> {
+    let hash_algorithm = payload_hash.algorithm();
+    let bytes = payload_hash.as_bytes();
+    ensure!(
+        bytes.len() == hash_algorithm.hash_size(),
+        PublishWithInvalidPayloadHashLength {
+            length: bytes.len(),
+            expected: hash_algorithm.hash_size()
+        }
+    );
+
+    let entry = unsigned_entry(
+        key_pair.public_key(),
         log_id,
-        is_end_of_feed,
-        payload_hash,
+        hash_algorithm.with_bytes(bytes.iter().copied().collect()),
         payload_size,
+        is_end_of_feed,
+        previous_seq_num,
+        lipmaa_entry_bytes,
+        backlink_bytes,
+    )?;
+
+    sign_entry(out, key_pair, entry)
+}
+
+/// Publish a new entry into the `out` buffer, reading the payload from `payload`.
+///
+/// Hashes the payload as it is read, so the whole payload never has to be in memory. Useful for
+/// big payloads like files. Takes the same arguments as [publish_with_hash_algorithm], except
+/// `payload` is read until it ends. The payload size is the number of bytes read.
+///
+/// Returns a `Result` of the size of the entry encoded into `out`.
+#[cfg(feature = "std")]
+#[allow(clippy::too_many_arguments)]
+pub fn publish_from_reader<K: EntrySigner + ?Sized, R: Read>(
+    out: &mut [u8],
+    key_pair: &K,
+    hash_algorithm: HashAlgorithm,
+    log_id: u64,
+    mut payload: R,
+    is_end_of_feed: bool,
+    previous_seq_num: Option<u64>,
+    lipmaa_entry_bytes: Option<&[u8]>,
+    backlink_bytes: Option<&[u8]>,
+) -> Result<usize, Error> {
+    let mut hasher = YamfHasher::new(hash_algorithm);
+    io::copy(&mut payload, &mut hasher).context(PublishReadPayload)?;
+
+    let entry = unsigned_entry(
+        key_pair.public_key(),
+        log_id,
+        hasher.finalize(),
+        hasher.bytes_hashed(),
+        is_end_of_feed,
+        previous_seq_num,
+        lipmaa_entry_bytes,
+        backlink_bytes,
+    )?;
+
+    sign_entry(out, key_pair, entry)
+}
+
+/// Publish a new entry into the `out` buffer using only the hashes of the previous entries.
+///
+/// Unlike [publish], this doesn't need the encoded lipmaa and backlink entries, so a writer only
+/// has to keep the hashes of the entries it might link to. That also means it can't check that
+/// the previous entries belong to the same author and log_id, the caller must make sure of that.
+///
+/// - `out`: A buffer to encode the entry into. Must be >= MAX_ENTRY_SIZE.
+/// - `key_pair`: The [EntrySigner] used to sign the entry, usually an ed25519 [Keypair].
+/// - `log_id`: The integer that distinguishes different logs by the same author.
+/// - `payload`: The payload of the entry. Note that only the hash of the payload becomes part of the entry. It's up to the caller to store the actual payload somewhere.
+/// - `is_end_of_feed`: Is this entry the final entry for this `log_id`?
+/// - `previous_seq_num`: The seq num of the previous entry. `None` if this is the first entry.
+/// - `lipmaa_link`: The hash of the lipmaa entry. Only needed when [is_lipmaa_required] is true for the new seq num.
+/// - `backlink`: The hash of the previous entry. `None` if this is the first entry.
+/// - `previous_is_end_of_feed`: Whether the previous entry set `is_end_of_feed`. Publishing after an end of feed entry is an error.
+///
+/// Returns a `Result` of the size of the entry encoded into `out`.
+#[allow(clippy::too_many_arguments)]
+pub fn publish_with_links<K: EntrySigner + ?Sized, H: Borrow<[u8]>>(
+    out: &mut [u8],
+    key_pair: &K,
+    log_id: u64,
+    payload: &[u8],
+    is_end_of_feed: bool,
+    previous_seq_num: Option<u64>,
+    lipmaa_link: Option<&YamfHash<H>>,
+    backlink: Option<&YamfHash<H>>,
+    previous_is_end_of_feed: bool,
+) -> Result<usize, Error> {
+    publish_with_links_and_hash_algorithm(
+        out,
+        key_pair,
+        HashAlgorithm::Blake2b,
+        log_id,
+        payload,
+        is_end_of_feed,
+        previous_seq_num,
+        lipmaa_link,
+        backlink,
+        previous_is_end_of_feed,
+    )
+}
+
+/// Publish a new entry into the `out` buffer using only the hashes of the previous entries,
+/// hashing the payload with `hash_algorithm`.
+///
+/// Takes the same arguments as [publish_with_links], which always uses [HashAlgorithm::Blake2b].
+/// The links must use `hash_algorithm` too, publishing with links of a different algorithm is an
+/// error.
+///
+/// Returns a `Result` of the size of the entry encoded into `out`.
+#[allow(clippy::too_many_arguments)]
+pub fn publish_with_links_and_hash_algorithm<K: EntrySigner + ?Sized, H: Borrow<[u8]>>(
+    out: &mut [u8],
+    key_pair: &K,
+    hash_algorithm: HashAlgorithm,
+    log_id: u64,
+    payload: &[u8],
+    is_end_of_feed: bool,
+    previous_seq_num: Option<u64>,
+    lipmaa_link: Option<&YamfHash<H>>,
+    backlink: Option<&YamfHash<H>>,
+    previous_is_end_of_feed: bool,
+) -> Result<usize, Error> {
+    lipmaa_link
+        .into_iter()
+        .chain(backlink)
+        .try_for_each(|link| check_hash_algorithm(hash_algorithm, link.algorithm()))?;
+
+    let payload_hash = hash_algorithm.hash(payload);
+
+    let entry = unsigned_entry_with_links(
+        key_pair.public_key(),
+        log_id,
+        (&payload_hash).into(),
+        payload.len() as u64,
+        is_end_of_feed,
+        previous_seq_num,
+        lipmaa_link.map(YamfHash::as_borrowed),
+        backlink.map(YamfHash::as_borrowed),
+        previous_is_end_of_feed,
+    )?;
+
+    sign_entry(out, key_pair, entry)
+}
+
+/// Build a new entry without signing it, for when the secret key lives somewhere else.
+///
+/// Encodes the bytes that need to be signed into the `out` buffer. Sign them with the secret key
+/// of `author` and pass the signature to [attach_signature] to finish publishing the entry.
+///
+/// Takes the same arguments as [publish], except only the public key of the author is needed.
+///
+/// Returns a `Result` of the size of the unsigned entry encoded into `out`.
+#[allow(clippy::too_many_arguments)]
+pub fn publish_unsigned(
+    out: &mut [u8],
+    author: &PublicKey,
+    log_id: u64,
+    payload: &[u8],
+    is_end_of_feed: bool,
+    previous_seq_num: Option<u64>,
+    lipmaa_entry_bytes: Option<&[u8]>,
+    backlink_bytes: Option<&[u8]>,
+) -> Result<usize, Error> {
+    publish_unsigned_with_hash_algorithm(
+        out,
         author,
-        seq_num,
-        backlink: None,
-        lipmaa_link: None,
-        sig: None,
-    };
+        HashAlgorithm::Blake2b,
+        log_id,
+        payload,
+        is_end_of_feed,
+        previous_seq_num,
+        lipmaa_entry_bytes,
+        backlink_bytes,
+    )
+}
 
-    // if the seq is larger than 1, we need to append the lipmaa and backlink hashes.
+/// Build a new entry without signing it like [publish_unsigned], hashing the payload and links
+/// with `hash_algorithm`.
+///
+/// Takes the same arguments as [publish_unsigned], which always uses [HashAlgorithm::Blake2b].
+/// Every entry in a log must use the same algorithm, so using a different algorithm to the
+/// previous entries is an error.
+///
+/// Returns a `Result` of the size of the unsigned entry encoded into `out`.
+#[allow(clippy::too_many_arguments)]
+pub fn publish_unsigned_with_hash_algorithm(
+    out: &mut [u8],
+    author: &PublicKey,
+    hash_algorithm: HashAlgorithm,
+    log_id: u64,
+    payload: &[u8],
+    is_end_of_feed: bool,
+    previous_seq_num: Option<u64>,
+    lipmaa_entry_bytes: Option<&[u8]>,
+    backlink_bytes: Option<&[u8]>,
+) -> Result<usize, Error> {
+    let entry: Entry<_, &[u8]> = unsigned_entry(
+        *author,
+        log_id,
+        hash_algorithm.hash(payload),
+        payload.len() as u64,
+        is_end_of_feed,
+        previous_seq_num,
+        lipmaa_entry_bytes,
+        backlink_bytes,
+    )?;
+
+    entry.encode(out).context(EncodeEntryToOutBuffer {
+        buffer_size: out.len(),
+    })
+}
+
+/// Attach a detached signature to an entry built by [publish_unsigned].
+///
+/// - `out`: The buffer [publish_unsigned] encoded into. Must have room for the signature too.
+/// - `unsigned_size`: The size returned by [publish_unsigned].
+/// - `signature`: The 64 byte ed25519 signature of `out[..unsigned_size]`.
+///
+/// The signature is checked against the author of the entry before it is accepted.
+///
+/// Returns a `Result` of the size of the signed entry encoded into `out`.
+pub fn attach_signature(
+    out: &mut [u8],
+    unsigned_size: usize,
+    signature: &[u8],
+) -> Result<usize, Error> {
+    ensure!(
+        signature.len() == ED25519_SIGNATURE_SIZE,
+        PublishWithInvalidSignatureLength {
+            length: signature.len()
+        }
+    );
+
+    let size = unsigned_size + ED25519_SIGNATURE_SIZE;
+    ensure!(
+        out.len() >= size,
+        PublishOutBufferTooSmall {
+            buffer_size: out.len(),
+            required_size: size
+        }
+    );
+
+    out[unsigned_size..size].copy_from_slice(signature);
+
+    let entry = decode(&out[..size]).context(DecodeSignedEntry)?;
+    entry
+        .verify_signature()
+        .context(PublishWithInvalidSignature)?;
+
+    Ok(size)
+}
+
+fn sign_entry<K: EntrySigner + ?Sized, H: Borrow<[u8]>>(
+    out: &mut [u8],
+    key_pair: &K,
+    mut entry: Entry<H, ArrayVec<[u8; ED25519_SIGNATURE_SIZE]>>,
+) -> Result<usize, Error> {
+    let buff_size = entry.encode(out).context(EncodeEntryToOutBuffer {
+        buffer_size: out.len(),
+    })?;
+
+    let signature = key_pair
+        .try_sign(&out[..buff_size])
+        .map_err(|_| NoneError)
+        .context(PublishSignFailed)?;
+    let signature = Signature(ArrayVec::from(signature.to_bytes()));
+
+    entry.sig = Some(signature);
+
+    entry.encode(out).context(EncodeEntryToOutBuffer {
+        buffer_size: out.len(),
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+fn unsigned_entry<S: Borrow<[u8]>>(
+    author: PublicKey,
+    log_id: u64,
+    payload_hash: YamfHash<ArrayVec<[u8; BLAKE2B_HASH_SIZE]>>,
+    payload_size: u64,
+    is_end_of_feed: bool,
+    previous_seq_num: Option<u64>,
+    lipmaa_entry_bytes: Option<&[u8]>,
+    backlink_bytes: Option<&[u8]>,
+) -> Result<Entry<ArrayVec<[u8; BLAKE2B_HASH_SIZE]>, S>, Error> {
+    let hash_algorithm = payload_hash.algorithm();
+    let seq_num = previous_seq_num.unwrap_or(0) + 1;
+    let mut previous_is_end_of_feed = false;
+
+    // if the seq is larger than 1, we need to check the lipmaa and backlink entries are part of
+    // the same feed.
     if seq_num > 1 {
         let backlink_entry = decode(&backlink_bytes.ok_or(Error::PublishWithoutBacklinkEntry)?[..])
             .context(DecodeBacklinkEntry)?;
 
         let lipmaa_entry = decode(&lipmaa_entry_bytes.ok_or(Error::PublishWithoutLipmaaEntry)?[..])
             .context(DecodeLipmaaEntry)?;
-        // Ensure we're not trying to publish after the end of a feed.
-        ensure!(!backlink_entry.is_end_of_feed, PublishAfterEndOfFeed);
+
+        previous_is_end_of_feed = backlink_entry.is_end_of_feed;
 
         // Avoid publishing to a feed using an incorrect log_id
         ensure!(
@@ -86,29 +455,71 @@ pub fn publish(
             PublishWithIncorrectLipmaaLinkLogId
         );
 
-        let backlink = new_blake2b(backlink_bytes.ok_or(Error::PublishWithoutBacklinkEntry)?);
-        entry.backlink = Some(backlink);
+        // Avoid mixing hash algorithms in a log
+        check_hash_algorithm(hash_algorithm, backlink_entry.payload_hash.algorithm())?;
+        check_hash_algorithm(hash_algorithm, lipmaa_entry.payload_hash.algorithm())?;
+    }
+
+    unsigned_entry_with_links(
+        author,
+        log_id,
+        payload_hash,
+        payload_size,
+        is_end_of_feed,
+        previous_seq_num,
+        lipmaa_entry_bytes.map(|bytes| hash_algorithm.hash(bytes)),
+        backlink_bytes.map(|bytes| hash_algorithm.hash(bytes)),
+        previous_is_end_of_feed,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn unsigned_entry_with_links<H: Borrow<[u8]>, S: Borrow<[u8]>>(
+    author: PublicKey,
+    log_id: u64,
+    payload_hash: YamfHash<H>,
+    payload_size: u64,
+    is_end_of_feed: bool,
+    previous_seq_num: Option<u64>,
+    lipmaa_link: Option<YamfHash<H>>,
+    backlink: Option<YamfHash<H>>,
+    previous_is_end_of_feed: bool,
+) -> Result<Entry<H, S>, Error> {
+    let seq_num = previous_seq_num.unwrap_or(0) + 1;
+
+    let mut entry = Entry {
+        log_id,
+        is_end_of_feed,
+        payload_hash,
+        payload_size,
+        author,
+        seq_num,
+        backlink: None,
+        lipmaa_link: None,
+        sig: None,
+    };
+
+    // if the seq is larger than 1, we need to append the lipmaa and backlink hashes.
+    if seq_num > 1 {
+        // Ensure we're not trying to publish after the end of a feed.
+        ensure!(!previous_is_end_of_feed, PublishAfterEndOfFeed);
+
+        entry.backlink = Some(backlink.ok_or(Error::PublishWithoutBacklinkEntry)?);
 
         // If the lipmaalink and backlink would be different, we should append the lipmaalink,
         // otherwise we're allowed to omit it to save some bytes.
         if is_lipmaa_required(seq_num) {
-            let lipmaa_link =
-                new_blake2b(lipmaa_entry_bytes.ok_or(Error::PublishWithoutLipmaaEntry)?);
-            entry.lipmaa_link = Some(lipmaa_link);
+            entry.lipmaa_link = Some(lipmaa_link.ok_or(Error::PublishWithoutLipmaaEntry)?);
         }
     }
 
-    let buff_size = entry.encode(out).context(EncodeEntryToOutBuffer {
-        buffer_size: out.len(),
-    })?;
-
-    let signature = key_pair.sign(&out[..buff_size]);
-    let sig_bytes = &signature.to_bytes()[..];
-    let signature = Signature(sig_bytes.into());
-
-    entry.sig = Some(signature);
+    Ok(entry)
+}
 
-    entry.encode(out).context(EncodeEntryToOutBuffer {
-        buffer_size: out.len(),
-    })
+fn check_hash_algorithm(expected: HashAlgorithm, actual: HashAlgorithm) -> Result<(), Error> {
+    ensure!(
+        expected == actual,
+        PublishWithDifferentHashAlgorithm { expected, actual }
+    );
+    Ok(())
 }
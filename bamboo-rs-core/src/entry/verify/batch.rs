@@ -1,94 +1,96 @@
-use crate::BLAKE2B_HASH_SIZE;
-use arrayvec::ArrayVec;
-use core::borrow::Borrow;
 use core::convert::TryFrom;
+use core::ops::RangeInclusive;
 use ed25519_dalek::PublicKey;
-use snafu::{NoneError, ResultExt};
+use snafu::{ensure, ResultExt};
 #[cfg(feature = "std")]
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
-use ed25519_dalek::Signature as DalekSignature;
-
-#[cfg(feature = "std")]
-use ed25519_dalek::verify_batch as verify_batch_dalek;
-
-use crate::yamf_hash::YamfHash;
-
-use super::verify_links_and_payload;
-use super::Entry;
+use super::batch_entry::{
+    verify_batch_entry_links_and_payload, verify_signatures_together, BatchEntry, FeedKey,
+};
+use super::{Entry, VerifyPolicy};
 use rayon::prelude::*;
 
-#[cfg(feature = "std")]
-use blake2b_simd::blake2b;
-
 use super::error::*;
 
 /// Batch verify a collection of entries that are **all from the same author and same log_id**
 ///
 /// Uses rayon and signature batch verification to utilize multiple processors + SIMD instruction.
+/// Entries from other feeds would be linked by seq num alone, use [verify_batch_multi_feed] for
+/// those.
 #[cfg(feature = "std")]
 pub fn verify_batch<E: AsRef<[u8]> + Sync, P: AsRef<[u8]> + Sync>(
     entries_and_payloads: &[(E, Option<P>)],
 ) -> Result<()> {
-    verify_batch_links_and_payload(entries_and_payloads)?;
-    let bytes_iter = entries_and_payloads
-        .iter()
-        .map(|(bytes, _)| bytes.as_ref())
-        .collect::<Vec<_>>();
-    verify_batch_signatures(&bytes_iter)?;
-
-    Ok(())
+    verify_batch_with(entries_and_payloads, None::<&VerifyPolicy>, false)
 }
 /// Batch verify the links + payloads of a collection of entries that are **all from the same author and same log_id**
 #[cfg(feature = "std")]
 pub fn verify_batch_links_and_payload<E: AsRef<[u8]> + Sync, P: AsRef<[u8]> + Sync>(
     entries_and_payloads: &[(E, Option<P>)],
 ) -> Result<()> {
-    // Build a hashmap from seq num to bytes and hashes we need.
-    let hash_map = entries_and_payloads[..]
-        .par_iter()
-        .map(|(bytes, payload)| {
-            let entry = Entry::try_from(bytes.as_ref()).context(DecodeEntry)?;
-            let entry_hash = blake2b(bytes.as_ref()); //HashManyJob::new(&params, bytes.as_ref());
-
-            let payload_and_hash = payload
-                .as_ref()
-                .map(|payload| (payload.as_ref(), blake2b(payload.as_ref())));
-
-            Ok((
-                entry.seq_num,
-                (bytes.as_ref(), entry, entry_hash, payload_and_hash),
-            ))
-        })
-        .collect::<Result<HashMap<u64, (_, _, _, _)>>>()?;
+    let batch_entries = decode_batch_entries(entries_and_payloads, None::<&VerifyPolicy>)?;
+    verify_single_log_links_and_payload(&batch_entries)
+}
 
-    hash_map
-        .par_iter()
-        .map(|(seq_num, (_, entry, _, payload_and_hash))| {
-            let backlink_and_hash = hash_map.get(&(seq_num - 1)).map(
-                |(bytes, _, entry_hash, _)| -> (_, YamfHash<ArrayVec<[u8; BLAKE2B_HASH_SIZE]>>) {
-                    (*bytes, (*entry_hash).into())
-                },
-            );
+/// Batch verify a collection of entries from **any number of authors and log_ids**.
+///
+/// Entries are grouped by author and log_id and the links are checked within each group, so
+/// entries from different feeds can be interleaved. All the signatures are checked together, like
+/// [verify_batch].
+#[cfg(feature = "std")]
+pub fn verify_batch_multi_feed<E: AsRef<[u8]> + Sync, P: AsRef<[u8]> + Sync>(
+    entries_and_payloads: &[(E, Option<P>)],
+) -> Result<()> {
+    verify_batch_multi_feed_with(entries_and_payloads, None::<&VerifyPolicy>, false)
+}
 
-            let lipmaa_link_and_hash = hash_map.get(&(lipmaa_link::lipmaa(*seq_num))).map(
-                |(bytes, _, entry_hash, _)| -> (_, YamfHash<ArrayVec<[u8; BLAKE2B_HASH_SIZE]>>) {
-                    (*bytes, (*entry_hash).into())
-                },
-            );
+/// Batch verify the links + payloads of a collection of entries from **any number of authors and
+/// log_ids**.
+#[cfg(feature = "std")]
+pub fn verify_batch_multi_feed_links_and_payload<E: AsRef<[u8]> + Sync, P: AsRef<[u8]> + Sync>(
+    entries_and_payloads: &[(E, Option<P>)],
+) -> Result<()> {
+    let batch_entries = decode_batch_entries(entries_and_payloads, None::<&VerifyPolicy>)?;
+    verify_multi_feed_links_and_payload(&batch_entries)
+}
 
-            let payload_and_hash = payload_and_hash
-                .as_ref()
-                .map(|(payload, job)| (*payload, (*job).into()));
+/// Batch verify a collection of entries like [verify_batch], rejecting entries that `policy`
+/// doesn't allow.
+///
+/// Every entry is checked against the policy before any links or signatures are. A
+/// [strict](VerifyPolicy::strict) policy checks the signatures like [verify_batch_strict].
+#[cfg(feature = "std")]
+pub fn verify_batch_with_policy<E, P, A, L>(
+    entries_and_payloads: &[(E, Option<P>)],
+    policy: &VerifyPolicy<A, L>,
+) -> Result<()>
+where
+    E: AsRef<[u8]> + Sync,
+    P: AsRef<[u8]> + Sync,
+    A: AsRef<[PublicKey]> + Sync,
+    L: AsRef<[RangeInclusive<u64>]> + Sync,
+{
+    verify_batch_with(entries_and_payloads, Some(policy), policy.is_strict())
+}
 
-            verify_links_and_payload(
-                entry,
-                payload_and_hash,
-                lipmaa_link_and_hash,
-                backlink_and_hash,
-            )
-        })
-        .collect()
+/// Batch verify a collection of entries like [verify_batch_multi_feed], rejecting entries that
+/// `policy` doesn't allow.
+///
+/// Every entry is checked against the policy before any links or signatures are. A
+/// [strict](VerifyPolicy::strict) policy checks the signatures like [verify_batch_strict].
+#[cfg(feature = "std")]
+pub fn verify_batch_multi_feed_with_policy<E, P, A, L>(
+    entries_and_payloads: &[(E, Option<P>)],
+    policy: &VerifyPolicy<A, L>,
+) -> Result<()>
+where
+    E: AsRef<[u8]> + Sync,
+    P: AsRef<[u8]> + Sync,
+    A: AsRef<[PublicKey]> + Sync,
+    L: AsRef<[RangeInclusive<u64>]> + Sync,
+{
+    verify_batch_multi_feed_with(entries_and_payloads, Some(policy), policy.is_strict())
 }
 
 /// Batch verify the signatures of a collection of entries that are **all from the same author and same log_id**
@@ -98,6 +100,33 @@ where
     [T]: ParallelSlice<T>,
     T: Sync,
 {
+    verify_batch_signatures_with(entries_bytes, false)
+}
+
+/// Batch verify a collection of entries like [verify_batch], checking the signatures like
+/// [verify_strict](super::verify_strict).
+///
+/// Accepts and rejects exactly the same entries as verifying each one with
+/// [verify_strict](super::verify_strict).
+#[cfg(feature = "std")]
+pub fn verify_batch_strict<E: AsRef<[u8]> + Sync, P: AsRef<[u8]> + Sync>(
+    entries_and_payloads: &[(E, Option<P>)],
+) -> Result<()> {
+    verify_batch_with(entries_and_payloads, None::<&VerifyPolicy>, true)
+}
+
+/// Batch verify signatures like [verify_batch_signatures], rejecting weak author keys and
+/// malleable signatures like [Entry::verify_signature_strict].
+#[cfg(feature = "std")]
+pub fn verify_batch_signatures_strict<T: AsRef<[u8]> + Sync>(entries_bytes: &[T]) -> Result<()> {
+    verify_batch_signatures_with(entries_bytes, true)
+}
+
+#[cfg(feature = "std")]
+fn verify_batch_signatures_with<T: AsRef<[u8]> + Sync>(
+    entries_bytes: &[T],
+    strict: bool,
+) -> Result<()> {
     entries_bytes
         .as_parallel_slice()
         .par_chunks(125)
@@ -109,45 +138,319 @@ where
                     .map(|bytes| Entry::try_from(bytes.as_ref()).context(DecodeEntry))
                     .collect::<Result<Vec<_>>>()?;
 
-                let unsigned_encoding_vecs = entries
-                    .iter()
-                    .map(|entry| {
-                        // TODO more efficient?
-                        let mut vec = Vec::with_capacity(entry.encoding_length());
-                        entry
-                            .encode_for_signing_write(&mut vec)
-                            .context(EncodeEntryForSigning)?;
-                        Ok(vec)
-                    })
-                    .collect::<Result<Vec<Vec<u8>>>>()?;
-
-                let unsigned_encodings = unsigned_encoding_vecs
-                    .iter()
-                    .map(|entry| entry.as_ref())
-                    .collect::<Vec<_>>();
-
-                let signatures = entries
-                    .iter()
-                    .map(|entry| {
-                        let ssb_sig =
-                            DalekSignature::try_from(entry.sig.as_ref().unwrap().0.borrow())
-                                .map_err(|_| NoneError)
-                                .context(DecodeSigError)?;
-                        Ok(ssb_sig)
-                    })
-                    .collect::<Result<Vec<DalekSignature>>>()?;
-
-                let pub_keys = entries
-                    .iter()
-                    .map(|entry| entry.author.clone())
-                    .collect::<Vec<PublicKey>>();
-
-                verify_batch_dalek(&unsigned_encodings, &signatures, &pub_keys[..])
-                    .map_err(|_| NoneError)
-                    .context(InvalidSignature)?;
-
-                Ok(())
+                verify_signatures_together(&entries, strict)
             },
         )
         .try_reduce(|| (), |_, _| Ok(()))
 }
+
+// Verify a single log, decoding each entry once for the policy, links and signatures.
+#[cfg(feature = "std")]
+fn verify_batch_with<E, P, A, L>(
+    entries_and_payloads: &[(E, Option<P>)],
+    policy: Option<&VerifyPolicy<A, L>>,
+    strict: bool,
+) -> Result<()>
+where
+    E: AsRef<[u8]> + Sync,
+    P: AsRef<[u8]> + Sync,
+    A: AsRef<[PublicKey]> + Sync,
+    L: AsRef<[RangeInclusive<u64>]> + Sync,
+{
+    let batch_entries = decode_batch_entries(entries_and_payloads, policy)?;
+    verify_single_log_links_and_payload(&batch_entries)?;
+    verify_batch_entry_signatures(batch_entries, strict)
+}
+
+// Verify entries from any number of feeds, decoding each entry once for the policy, links and
+// signatures.
+#[cfg(feature = "std")]
+fn verify_batch_multi_feed_with<E, P, A, L>(
+    entries_and_payloads: &[(E, Option<P>)],
+    policy: Option<&VerifyPolicy<A, L>>,
+    strict: bool,
+) -> Result<()>
+where
+    E: AsRef<[u8]> + Sync,
+    P: AsRef<[u8]> + Sync,
+    A: AsRef<[PublicKey]> + Sync,
+    L: AsRef<[RangeInclusive<u64>]> + Sync,
+{
+    let batch_entries = decode_batch_entries(entries_and_payloads, policy)?;
+    verify_multi_feed_links_and_payload(&batch_entries)?;
+    verify_batch_entry_signatures(batch_entries, strict)
+}
+
+// Verify the signatures of entries that have already been decoded, in chunks like
+// `verify_batch_signatures`.
+#[cfg(feature = "std")]
+fn verify_batch_entry_signatures(batch_entries: Vec<BatchEntry>, strict: bool) -> Result<()> {
+    let entries = batch_entries
+        .into_iter()
+        .map(|batch_entry| batch_entry.entry)
+        .collect::<Vec<_>>();
+
+    entries
+        .par_chunks(125)
+        .try_for_each(|chunk| verify_signatures_together(chunk, strict))
+}
+
+/// Batch verify a collection of entries from **any number of authors and log_ids**, returning a
+/// result for each entry.
+///
+/// The results are in the same order as `entries_and_payloads`, so one bad entry doesn't stop
+/// the valid ones from being accepted. Entries are grouped by feed like
+/// [verify_batch_multi_feed]. An entry whose links or payload are invalid gets that error and its
+/// signature isn't checked. Entries that share a seq num with another entry in their feed all get
+/// [Error::DuplicateSeqNum].
+#[cfg(feature = "std")]
+pub fn verify_batch_report<E: AsRef<[u8]> + Sync, P: AsRef<[u8]> + Sync>(
+    entries_and_payloads: &[(E, Option<P>)],
+) -> Vec<Result<()>> {
+    verify_batch_report_with(entries_and_payloads, None::<&VerifyPolicy>, false)
+}
+
+/// Batch verify a collection of entries like [verify_batch_report], checking the signatures like
+/// [verify_strict](super::verify_strict).
+#[cfg(feature = "std")]
+pub fn verify_batch_report_strict<E: AsRef<[u8]> + Sync, P: AsRef<[u8]> + Sync>(
+    entries_and_payloads: &[(E, Option<P>)],
+) -> Vec<Result<()>> {
+    verify_batch_report_with(entries_and_payloads, None::<&VerifyPolicy>, true)
+}
+
+/// Batch verify a collection of entries like [verify_batch_report], rejecting entries that
+/// `policy` doesn't allow.
+///
+/// An entry the policy doesn't allow gets that error and isn't used to check the links of the
+/// others. A [strict](VerifyPolicy::strict) policy checks the signatures like
+/// [verify_batch_report_strict].
+#[cfg(feature = "std")]
+pub fn verify_batch_report_with_policy<E, P, A, L>(
+    entries_and_payloads: &[(E, Option<P>)],
+    policy: &VerifyPolicy<A, L>,
+) -> Vec<Result<()>>
+where
+    E: AsRef<[u8]> + Sync,
+    P: AsRef<[u8]> + Sync,
+    A: AsRef<[PublicKey]> + Sync,
+    L: AsRef<[RangeInclusive<u64>]> + Sync,
+{
+    verify_batch_report_with(entries_and_payloads, Some(policy), policy.is_strict())
+}
+
+#[cfg(feature = "std")]
+fn verify_batch_report_with<E, P, A, L>(
+    entries_and_payloads: &[(E, Option<P>)],
+    policy: Option<&VerifyPolicy<A, L>>,
+    strict: bool,
+) -> Vec<Result<()>>
+where
+    E: AsRef<[u8]> + Sync,
+    P: AsRef<[u8]> + Sync,
+    A: AsRef<[PublicKey]> + Sync,
+    L: AsRef<[RangeInclusive<u64>]> + Sync,
+{
+    let decoded = entries_and_payloads[..]
+        .par_iter()
+        .map(|(bytes, payload)| {
+            BatchEntry::new_with_policy(bytes.as_ref(), payload.as_ref(), policy)
+        })
+        .collect::<Vec<_>>();
+
+    let mut results = Vec::with_capacity(decoded.len());
+    let mut batch_entries = Vec::with_capacity(decoded.len());
+    for (index, batch_entry) in decoded.into_iter().enumerate() {
+        match batch_entry {
+            Ok(batch_entry) => {
+                results.push(Ok(()));
+                batch_entries.push((index, batch_entry));
+            }
+            Err(err) => results.push(Err(err)),
+        }
+    }
+
+    // Group into logs by author and log_id, then by seq num within each log. Entries that share a
+    // seq num with another entry in their log are left out, so nothing links to either of them.
+    let mut logs = HashMap::<FeedKey, HashMap<u64, &BatchEntry>>::new();
+    let mut duplicates = HashSet::<(FeedKey, u64)>::new();
+    for (_, batch_entry) in batch_entries.iter() {
+        let feed_key = batch_entry.feed_key();
+        let seq_num = batch_entry.entry.seq_num;
+        let log = logs.entry(feed_key).or_default();
+        if duplicates.contains(&(feed_key, seq_num)) || log.remove(&seq_num).is_some() {
+            duplicates.insert((feed_key, seq_num));
+        } else {
+            log.insert(seq_num, batch_entry);
+        }
+    }
+
+    let link_results = batch_entries
+        .par_iter()
+        .map(|(_, batch_entry)| {
+            let feed_key = batch_entry.feed_key();
+            let seq_num = batch_entry.entry.seq_num;
+            ensure!(
+                !duplicates.contains(&(feed_key, seq_num)),
+                DuplicateSeqNum { seq_num }
+            );
+
+            let log = &logs[&feed_key];
+            verify_batch_entry_links_and_payload(batch_entry, |seq_num| log.get(&seq_num))
+        })
+        .collect::<Vec<_>>();
+
+    let mut signed_indices = Vec::with_capacity(batch_entries.len());
+    let mut signed_entries = Vec::with_capacity(batch_entries.len());
+    for ((index, batch_entry), link_result) in batch_entries.into_iter().zip(link_results) {
+        match link_result {
+            Ok(()) => {
+                signed_indices.push(index);
+                signed_entries.push(batch_entry.entry);
+            }
+            Err(err) => results[index] = Err(err),
+        }
+    }
+
+    let signature_results = verify_entry_signatures_report(&signed_entries, strict);
+    for (index, signature_result) in signed_indices.into_iter().zip(signature_results) {
+        results[index] = signature_result;
+    }
+
+    results
+}
+
+/// Batch verify the signatures of a collection of entries, returning a result for each entry.
+///
+/// The results are in the same order as `entries_bytes`. When a batch of signatures fails it is
+/// split in half and each half is retried, until the invalid signatures are found.
+#[cfg(feature = "std")]
+pub fn verify_batch_signatures_report<T: AsRef<[u8]> + Sync>(
+    entries_bytes: &[T],
+) -> Vec<Result<()>> {
+    let decoded = entries_bytes
+        .par_iter()
+        .map(|bytes| Entry::try_from(bytes.as_ref()).context(DecodeEntry))
+        .collect::<Vec<_>>();
+
+    let mut results = Vec::with_capacity(decoded.len());
+    let mut indices = Vec::with_capacity(decoded.len());
+    let mut entries = Vec::with_capacity(decoded.len());
+    for (index, entry) in decoded.into_iter().enumerate() {
+        match entry {
+            Ok(entry) => {
+                results.push(Ok(()));
+                indices.push(index);
+                entries.push(entry);
+            }
+            Err(err) => results.push(Err(err)),
+        }
+    }
+
+    let signature_results = verify_entry_signatures_report(&entries, false);
+    for (index, signature_result) in indices.into_iter().zip(signature_results) {
+        results[index] = signature_result;
+    }
+
+    results
+}
+
+fn verify_entry_signatures_report(
+    entries: &[Entry<&[u8], &[u8]>],
+    strict: bool,
+) -> Vec<Result<()>> {
+    let mut results = entries.iter().map(|_| Ok(())).collect::<Vec<_>>();
+
+    entries
+        .par_chunks(125)
+        .zip(results.par_chunks_mut(125))
+        .for_each(|(entries, results)| bisect_signatures(entries, results, strict));
+
+    results
+}
+
+// Verify the signatures of `entries` together, splitting them in half and retrying each half if
+// any are invalid. Each result in `results` is set for the entry at the same index.
+fn bisect_signatures(entries: &[Entry<&[u8], &[u8]>], results: &mut [Result<()>], strict: bool) {
+    match entries.len() {
+        0 => {}
+        1 if strict => results[0] = entries[0].verify_signature_strict(),
+        1 => results[0] = entries[0].verify_signature(),
+        _ => {
+            if verify_signatures_together(entries, strict).is_err() {
+                let middle = entries.len() / 2;
+                let (left_entries, right_entries) = entries.split_at(middle);
+                let (left_results, right_results) = results.split_at_mut(middle);
+                rayon::join(
+                    || bisect_signatures(left_entries, left_results, strict),
+                    || bisect_signatures(right_entries, right_results, strict),
+                );
+            }
+        }
+    }
+}
+
+// Decode and hash every entry, checking each against `policy` if there is one.
+fn decode_batch_entries<'a, E, P, A, L>(
+    entries_and_payloads: &'a [(E, Option<P>)],
+    policy: Option<&VerifyPolicy<A, L>>,
+) -> Result<Vec<BatchEntry<'a>>>
+where
+    E: AsRef<[u8]> + Sync,
+    P: AsRef<[u8]> + Sync,
+    A: AsRef<[PublicKey]> + Sync,
+    L: AsRef<[RangeInclusive<u64>]> + Sync,
+{
+    entries_and_payloads[..]
+        .par_iter()
+        .map(|(bytes, payload)| {
+            BatchEntry::new_with_policy(bytes.as_ref(), payload.as_ref(), policy)
+        })
+        .collect()
+}
+
+// Verify the links + payloads of entries that are all from the same author and log_id.
+fn verify_single_log_links_and_payload(batch_entries: &[BatchEntry]) -> Result<()> {
+    // Build a hashmap from seq num to bytes and hashes we need.
+    let mut log = HashMap::new();
+    for batch_entry in batch_entries {
+        insert_log_entry(&mut log, batch_entry)?;
+    }
+
+    verify_log_links_and_payload(&log)
+}
+
+// Verify the links + payloads of entries from any number of feeds.
+fn verify_multi_feed_links_and_payload(batch_entries: &[BatchEntry]) -> Result<()> {
+    // Group into logs by author and log_id, then by seq num within each log.
+    let mut logs = HashMap::<FeedKey, HashMap<u64, &BatchEntry>>::new();
+    for batch_entry in batch_entries {
+        insert_log_entry(logs.entry(batch_entry.feed_key()).or_default(), batch_entry)?;
+    }
+
+    logs.par_iter()
+        .map(|(_, log)| verify_log_links_and_payload(log))
+        .collect()
+}
+
+// Verify the links + payloads of the entries in a single log, keyed by seq num.
+fn verify_log_links_and_payload(log: &HashMap<u64, &BatchEntry>) -> Result<()> {
+    log.par_iter()
+        .map(|(_, batch_entry)| {
+            verify_batch_entry_links_and_payload(batch_entry, |seq_num| log.get(&seq_num))
+        })
+        .collect()
+}
+
+// Add `batch_entry` to `log`. Two entries with the same seq num can't both be in the log, and
+// only one of them would have its links and payload checked, so that's an error.
+fn insert_log_entry<'a, 'b>(
+    log: &mut HashMap<u64, &'b BatchEntry<'a>>,
+    batch_entry: &'b BatchEntry<'a>,
+) -> Result<()> {
+    let seq_num = batch_entry.entry.seq_num;
+    ensure!(!log.contains_key(&seq_num), DuplicateSeqNum { seq_num });
+    log.insert(seq_num, batch_entry);
+
+    Ok(())
+}
@@ -0,0 +1,188 @@
+//! Decoding, link checking and signature checking shared by the batch verifiers.
+#[cfg(feature = "std")]
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::convert::TryFrom;
+use core::ops::RangeInclusive;
+#[cfg(feature = "std")]
+use ed25519_dalek::verify_batch as verify_batch_dalek;
+#[cfg(feature = "std")]
+use ed25519_dalek::Signature as DalekSignature;
+use ed25519_dalek::{PublicKey, PUBLIC_KEY_LENGTH};
+use snafu::ResultExt;
+#[cfg(feature = "std")]
+use snafu::{NoneError, OptionExt};
+
+use super::error::*;
+#[cfg(feature = "std")]
+use super::strict::check_strict;
+use super::{verify_links_and_payload, VerifyPolicy};
+use crate::entry::owned::OwnedYamfHash;
+use crate::entry::Entry;
+use crate::yamf_hash::YamfHash;
+
+pub(crate) type FeedKey = ([u8; PUBLIC_KEY_LENGTH], u64);
+
+// An entry in a batch, decoded and hashed ready to have its links checked.
+pub(crate) struct BatchEntry<'a> {
+    pub bytes: &'a [u8],
+    pub entry: Entry<&'a [u8], &'a [u8]>,
+    pub hash: OwnedYamfHash,
+    pub payload_and_hash: Option<(&'a [u8], OwnedYamfHash)>,
+}
+
+impl<'a> BatchEntry<'a> {
+    pub fn new<P: AsRef<[u8]>>(bytes: &'a [u8], payload: Option<&'a P>) -> Result<BatchEntry<'a>> {
+        BatchEntry::new_with_policy(bytes, payload, None::<&VerifyPolicy>)
+    }
+
+    // Decode an entry, rejecting it if there's a `policy` that doesn't allow it.
+    pub fn new_with_policy<P, A, L>(
+        bytes: &'a [u8],
+        payload: Option<&'a P>,
+        policy: Option<&VerifyPolicy<A, L>>,
+    ) -> Result<BatchEntry<'a>>
+    where
+        P: AsRef<[u8]>,
+        A: AsRef<[PublicKey]>,
+        L: AsRef<[RangeInclusive<u64>]>,
+    {
+        let entry = Entry::try_from(bytes).context(DecodeEntry)?;
+        if let Some(policy) = policy {
+            policy.check(&entry)?;
+        }
+        Ok(BatchEntry::from_entry(bytes, entry, payload))
+    }
+
+    // Hash an entry that's already been decoded from `bytes`.
+    fn from_entry<P: AsRef<[u8]>>(
+        bytes: &'a [u8],
+        entry: Entry<&'a [u8], &'a [u8]>,
+        payload: Option<&'a P>,
+    ) -> BatchEntry<'a> {
+        // Entries in a log are linked with the same algorithm as their payloads, so this is
+        // almost always the hash that the next entries need.
+        let hash_algorithm = entry.payload_hash.algorithm();
+        let hash = hash_algorithm.hash(bytes);
+
+        let payload_and_hash =
+            payload.map(|payload| (payload.as_ref(), hash_algorithm.hash(payload.as_ref())));
+
+        BatchEntry {
+            bytes,
+            entry,
+            hash,
+            payload_and_hash,
+        }
+    }
+
+    pub fn feed_key(&self) -> FeedKey {
+        (self.entry.author.to_bytes(), self.entry.log_id)
+    }
+}
+
+// Verify the links + payload of a single entry. `find` looks up the other entries in its log by
+// seq num.
+pub(crate) fn verify_batch_entry_links_and_payload<'a, 'b, B, F>(
+    batch_entry: &BatchEntry,
+    find: F,
+) -> Result<()>
+where
+    'a: 'b,
+    B: Borrow<BatchEntry<'a>> + 'b,
+    F: Fn(u64) -> Option<&'b B>,
+{
+    let entry = &batch_entry.entry;
+    let seq_num = entry.seq_num;
+
+    let backlink_and_hash = find(seq_num - 1).map(|linked| {
+        let linked = linked.borrow();
+        (
+            linked.bytes,
+            link_hash(entry.backlink.as_ref(), linked.bytes, &linked.hash),
+        )
+    });
+
+    let lipmaa_link_and_hash = find(lipmaa_link::lipmaa(seq_num)).map(|linked| {
+        let linked = linked.borrow();
+        (
+            linked.bytes,
+            link_hash(entry.lipmaa_link.as_ref(), linked.bytes, &linked.hash),
+        )
+    });
+
+    let payload_and_hash = batch_entry
+        .payload_and_hash
+        .as_ref()
+        .map(|(payload, hash)| (*payload, hash.clone()));
+
+    verify_links_and_payload(
+        entry,
+        payload_and_hash,
+        lipmaa_link_and_hash,
+        backlink_and_hash,
+    )
+}
+
+// The hash of a linked entry, using the algorithm of the link that points at it.
+//
+// Reuses the hash made when the entry was decoded unless the link uses a different algorithm.
+fn link_hash(
+    link: Option<&YamfHash<&[u8]>>,
+    bytes: &[u8],
+    entry_hash: &OwnedYamfHash,
+) -> OwnedYamfHash {
+    match link.map(YamfHash::algorithm) {
+        Some(algorithm) if algorithm != entry_hash.algorithm() => algorithm.hash(bytes),
+        _ => entry_hash.clone(),
+    }
+}
+
+// Verify the signatures of `entries` in a single batch. `strict` rejects the same entries as
+// `Entry::verify_signature_strict`.
+#[cfg(feature = "std")]
+pub(crate) fn verify_signatures_together(
+    entries: &[Entry<&[u8], &[u8]>],
+    strict: bool,
+) -> Result<()> {
+    if strict {
+        entries.iter().try_for_each(check_strict)?;
+    }
+
+    let unsigned_encoding_vecs = entries
+        .iter()
+        .map(|entry| {
+            let mut vec = alloc::vec![0u8; entry.encoding_length()];
+            let size = entry
+                .encode_for_signing(&mut vec)
+                .context(EncodeEntryForSigning)?;
+            vec.truncate(size);
+            Ok(vec)
+        })
+        .collect::<Result<Vec<Vec<u8>>>>()?;
+
+    let unsigned_encodings = unsigned_encoding_vecs
+        .iter()
+        .map(|entry| entry.as_ref())
+        .collect::<Vec<_>>();
+
+    let signatures = entries
+        .iter()
+        .map(|entry| {
+            let sig = entry.sig.as_ref().context(InvalidSignature)?;
+            let ssb_sig = DalekSignature::try_from(sig.0)
+                .map_err(|_| NoneError)
+                .context(DecodeSigError)?;
+            Ok(ssb_sig)
+        })
+        .collect::<Result<Vec<DalekSignature>>>()?;
+
+    let pub_keys = entries
+        .iter()
+        .map(|entry| entry.author)
+        .collect::<Vec<PublicKey>>();
+
+    verify_batch_dalek(&unsigned_encodings, &signatures, &pub_keys[..])
+        .map_err(|_| NoneError)
+        .context(InvalidSignature)
+}
@@ -0,0 +1,119 @@
+//! ed25519 batch verification using a random number generator supplied by the caller.
+//!
+//! `ed25519-dalek` only batch verifies with `std`'s thread rng, so targets with just `alloc` need
+//! to bring their own. This is the same check `ed25519_dalek::verify_batch` makes.
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::iter::once;
+use curve25519_dalek::constants::ED25519_BASEPOINT_POINT;
+use curve25519_dalek::edwards::{CompressedEdwardsY, EdwardsPoint};
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::{IsIdentity, VartimeMultiscalarMul};
+use ed25519_dalek::{PUBLIC_KEY_LENGTH, SIGNATURE_LENGTH};
+use rand_core::{CryptoRng, RngCore};
+use sha2::{Digest, Sha512};
+use snafu::{ensure, OptionExt, ResultExt};
+
+use super::error::*;
+use super::strict::check_strict;
+use crate::entry::Entry;
+
+// A decoded signature, split into its R point and s scalar.
+struct DecodedSignature {
+    r: CompressedEdwardsY,
+    s: Scalar,
+}
+
+impl DecodedSignature {
+    fn decode(sig: &[u8]) -> Option<DecodedSignature> {
+        let sig = <[u8; SIGNATURE_LENGTH]>::try_from(sig).ok()?;
+        let mut r = [0u8; PUBLIC_KEY_LENGTH];
+        r.copy_from_slice(&sig[..PUBLIC_KEY_LENGTH]);
+        let mut s = [0u8; 32];
+        s.copy_from_slice(&sig[PUBLIC_KEY_LENGTH..]);
+
+        // Accept the same s values as `ed25519-dalek`: if the top 4 bits are unset the scalar is
+        // already reduced, otherwise it has to be canonical.
+        let s = if s[31] & 240 == 0 {
+            Scalar::from_bits(s)
+        } else {
+            Scalar::from_canonical_bytes(s)?
+        };
+
+        Some(DecodedSignature {
+            r: CompressedEdwardsY(r),
+            s,
+        })
+    }
+}
+
+// Verify the signatures of `entries` in a single batch, picking the random coefficients from
+// `rng`. `strict` rejects the same entries as `Entry::verify_signature_strict`.
+pub(crate) fn verify_signatures_with_rng<R: RngCore + CryptoRng>(
+    entries: &[Entry<&[u8], &[u8]>],
+    strict: bool,
+    rng: &mut R,
+) -> Result<()> {
+    if strict {
+        entries.iter().try_for_each(check_strict)?;
+    }
+
+    let signatures = entries
+        .iter()
+        .map(|entry| {
+            let sig = entry.sig.as_ref().context(InvalidSignature)?;
+            DecodedSignature::decode(sig.0).context(DecodeSigError)
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    // H(R || A || M) for each entry, where M is the entry encoded for signing.
+    let mut unsigned_encoding = [0u8; crate::entry::MAX_ENTRY_SIZE];
+    let hrams = entries
+        .iter()
+        .zip(signatures.iter())
+        .map(|(entry, sig)| {
+            let size = entry
+                .encode_for_signing(&mut unsigned_encoding)
+                .context(EncodeEntryForSigning)?;
+            let mut h = Sha512::default();
+            h.update(sig.r.as_bytes());
+            h.update(entry.author.as_bytes());
+            h.update(&unsigned_encoding[..size]);
+            Ok(Scalar::from_hash(h))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    // A random 128 bit coefficient for each signature.
+    let zs = signatures
+        .iter()
+        .map(|_| {
+            let mut z = [0u8; 16];
+            rng.fill_bytes(&mut z);
+            Scalar::from(u128::from_le_bytes(z))
+        })
+        .collect::<Vec<_>>();
+
+    let b_coefficient: Scalar = signatures
+        .iter()
+        .zip(zs.iter())
+        .map(|(sig, z)| z * sig.s)
+        .sum();
+
+    let zhrams = hrams.iter().zip(zs.iter()).map(|(hram, z)| hram * z);
+
+    let rs = signatures.iter().map(|sig| sig.r.decompress());
+    let authors = entries
+        .iter()
+        .map(|entry| CompressedEdwardsY(entry.author.to_bytes()).decompress());
+
+    // (-∑ z[i]s[i]) B + ∑ z[i]R[i] + ∑ (z[i]H(R||A||M)[i]) A[i] = 0
+    let id = EdwardsPoint::optional_multiscalar_mul(
+        once(-b_coefficient).chain(zs.iter().cloned()).chain(zhrams),
+        once(Some(ED25519_BASEPOINT_POINT)).chain(rs).chain(authors),
+    )
+    .context(InvalidSignature)?;
+
+    ensure!(id.is_identity(), InvalidSignature);
+
+    Ok(())
+}
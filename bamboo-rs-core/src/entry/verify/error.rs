@@ -53,6 +53,42 @@ pub enum Error {
     EncodeEntryForSigning { source: EncodeError },
     #[snafu(display("The entry is invalid."))]
     UnknownError,
+    #[cfg_attr(
+        feature = "std",
+        snafu(display("Failed to read the payload: {}", source))
+    )]
+    #[cfg_attr(not(feature = "std"), snafu(display("Failed to read the payload")))]
+    ReadPayload {
+        #[cfg(feature = "std")]
+        source: std::io::Error,
+    },
+    #[snafu(display("The author key is a weak ed25519 key"))]
+    WeakAuthorKey,
+    #[snafu(display("The entry signature is malleable"))]
+    WeakSignature,
+    #[snafu(display(
+        "The payload size {} is bigger than the policy allows, max: {}",
+        size,
+        max
+    ))]
+    PayloadTooLarge { size: u64, max: u64 },
+    #[snafu(display(
+        "The seq num {} is bigger than the policy allows, max: {}",
+        seq_num,
+        max
+    ))]
+    SeqNumTooLarge { seq_num: u64, max: u64 },
+    #[snafu(display("The policy doesn't allow entries by this author"))]
+    AuthorNotAllowed,
+    #[snafu(display("The policy doesn't allow entries with log_id {}", log_id))]
+    LogIdNotAllowed { log_id: u64 },
+    #[snafu(display("The policy doesn't allow end of feed entries"))]
+    EndOfFeedNotAllowed,
+    #[snafu(display(
+        "The batch has more than one entry with seq num {} in the same feed",
+        seq_num
+    ))]
+    DuplicateSeqNum { seq_num: u64 },
 }
 
 pub type Result<T, E = Error> = core::result::Result<T, E>;
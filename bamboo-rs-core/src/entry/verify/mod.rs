@@ -1,22 +1,58 @@
 use arrayvec::ArrayVec;
 use core::borrow::Borrow;
 use core::convert::TryFrom;
-use snafu::{ensure, NoneError, ResultExt};
+use core::ops::RangeInclusive;
+use snafu::{ensure, NoneError, OptionExt, ResultExt};
 
-use ed25519_dalek::{Signature as DalekSignature, Verifier};
+use ed25519_dalek::{PublicKey, Signature as DalekSignature, Verifier};
 
-use super::{decode::decode, is_lipmaa_required, Entry};
-use crate::yamf_hash::new_blake2b;
+use super::decode::{decode, decode_with_length, EntryWithLength};
+use super::owned::OwnedYamfHash;
+use super::{is_lipmaa_required, Entry};
 use crate::yamf_hash::YamfHash;
+#[cfg(feature = "std")]
+use crate::yamf_hash::YamfHasher;
+#[cfg(feature = "std")]
+use std::io::{self, Read};
+
+#[cfg(feature = "alloc")]
+mod batch_entry;
+#[cfg(feature = "alloc")]
+mod ed25519_batch;
 
 #[cfg(feature = "std")]
 pub mod batch;
 #[cfg(feature = "std")]
-pub use batch::{verify_batch, verify_batch_signatures};
+pub use batch::{
+    verify_batch, verify_batch_multi_feed, verify_batch_multi_feed_with_policy,
+    verify_batch_report, verify_batch_report_strict, verify_batch_report_with_policy,
+    verify_batch_signatures, verify_batch_signatures_report, verify_batch_signatures_strict,
+    verify_batch_strict, verify_batch_with_policy,
+};
+
+#[cfg(feature = "alloc")]
+pub mod sequential;
+#[cfg(feature = "alloc")]
+pub use sequential::{
+    verify_batch_sequential, verify_batch_sequential_strict, verify_batch_sequential_with_policy,
+    verify_batch_signatures_sequential,
+};
 
 pub mod error;
 pub use error::*;
 
+pub mod policy;
+#[cfg(feature = "alloc")]
+pub use policy::OwnedVerifyPolicy;
+pub use policy::VerifyPolicy;
+
+pub(crate) mod strict;
+use strict::check_strict;
+pub use strict::is_weak_key;
+
+pub mod verified;
+pub use verified::{VerifiedChecks, VerifiedEntry};
+
 impl<'a, H, S> Entry<H, S>
 where
     H: Borrow<[u8]>,
@@ -24,7 +60,8 @@ where
 {
     /// Verify the signature of an entry is valid.
     pub fn verify_signature(&self) -> Result<()> {
-        let ssb_sig = DalekSignature::try_from(self.sig.as_ref().unwrap().0.borrow())
+        let sig = self.sig.as_ref().context(InvalidSignature)?;
+        let ssb_sig = DalekSignature::try_from(sig.0.borrow())
             .map_err(|_| NoneError)
             .context(DecodeSigError)?;
 
@@ -39,6 +76,29 @@ where
             .map_err(|_| NoneError)
             .context(InvalidSignature)
     }
+
+    /// Verify the signature of an entry is valid, with `verify_strict` semantics.
+    ///
+    /// Unlike [verify_signature](Entry::verify_signature), this rejects weak author keys and
+    /// malleable signatures, so each entry has exactly one valid encoding. Use it when entries are
+    /// addressed by their hash.
+    pub fn verify_signature_strict(&self) -> Result<()> {
+        check_strict(self)?;
+
+        let sig = self.sig.as_ref().context(InvalidSignature)?;
+        let ssb_sig = DalekSignature::try_from(sig.0.borrow())
+            .map_err(|_| NoneError)
+            .context(DecodeSigError)?;
+
+        let mut buff = [0u8; 512];
+
+        let encoded_size = self.encode_for_signing(&mut buff).unwrap();
+
+        self.author
+            .verify_strict(&buff[..encoded_size], &ssb_sig)
+            .map_err(|_| NoneError)
+            .context(InvalidSignature)
+    }
 }
 
 pub fn verify_links_and_payload(
@@ -46,20 +106,41 @@ pub fn verify_links_and_payload(
     payload: Option<(&[u8], YamfHash<ArrayVec<[u8; 64]>>)>,
     lipmaa_link: Option<(&[u8], YamfHash<ArrayVec<[u8; 64]>>)>,
     backlink: Option<(&[u8], YamfHash<ArrayVec<[u8; 64]>>)>,
+) -> Result<(), Error> {
+    let payload_hash_and_size =
+        payload.map(|(payload, payload_hash)| (payload_hash, payload.len() as u64));
+
+    verify_links_and_payload_hash(entry, payload_hash_and_size, lipmaa_link, backlink)
+}
+
+fn verify_links_and_payload_hash<P: Borrow<[u8]>>(
+    entry: &Entry<&[u8], &[u8]>,
+    payload_hash_and_size: Option<(YamfHash<P>, u64)>,
+    lipmaa_link: Option<(&[u8], OwnedYamfHash)>,
+    backlink: Option<(&[u8], OwnedYamfHash)>,
 ) -> Result<(), Error> {
     // If we have the payload, check that its hash and length match what is encoded in the
     // entry.
-    if let Some((payload, payload_hash)) = payload {
+    if let Some((payload_hash, payload_size)) = payload_hash_and_size {
         ensure!(payload_hash == entry.payload_hash, PayloadHashDidNotMatch);
         ensure!(
-            payload.len() == entry.payload_size as usize,
+            payload_size == entry.payload_size,
             PayloadLengthDidNotMatch {
-                actual: payload.len(),
+                actual: payload_size as usize,
                 expected: entry.payload_size
             }
         );
     }
 
+    verify_lipmaa_link(entry, lipmaa_link)?;
+    verify_backlink(entry, backlink)
+}
+
+/// Check `lipmaa_link`, the encoded lipmaa entry and its hash, against `entry`.
+pub(crate) fn verify_lipmaa_link(
+    entry: &Entry<&[u8], &[u8]>,
+    lipmaa_link: Option<(&[u8], OwnedYamfHash)>,
+) -> Result<(), Error> {
     let lipmaa_is_required = is_lipmaa_required(entry.seq_num);
 
     match (
@@ -98,8 +179,14 @@ pub fn verify_links_and_payload(
         (_, _, seq_num, false) if seq_num > 1 => Ok(()),
         (None, _, _, true) => Err(Error::LipmaaLinkRequired),
         (_, _, _, _) => Err(Error::UnknownError),
-    }?;
+    }
+}
 
+/// Check `backlink`, the encoded backlink entry and its hash, against `entry`.
+pub(crate) fn verify_backlink(
+    entry: &Entry<&[u8], &[u8]>,
+    backlink: Option<(&[u8], OwnedYamfHash)>,
+) -> Result<(), Error> {
     match (backlink, entry.backlink.as_ref(), entry.seq_num) {
         // Happy path 1: This is the first entry and doesn't have a backlink.
         (_, None, seq_num) if seq_num == 1 => Ok(()),
@@ -135,9 +222,7 @@ pub fn verify_links_and_payload(
         //replication.
         (None, Some(_), seq_num) if seq_num > 1 => Ok(()),
         (_, _, _) => Err(Error::UnknownError),
-    }?;
-
-    Ok(())
+    }
 }
 
 /// verify `entry_bytes` is valid.
@@ -159,19 +244,185 @@ pub fn verify(
     lipmaa_link: Option<&[u8]>,
     backlink: Option<&[u8]>,
 ) -> Result<(), Error> {
-    // Decode the entry that we want to verify.
+    verify_and_decode(entry_bytes, payload, lipmaa_link, backlink, false).map(|_| ())
+}
+
+/// Verify `entry_bytes` like [verify], checking the signature with
+/// [verify_signature_strict](Entry::verify_signature_strict).
+///
+/// Rejects weak author keys and malleable signatures that [verify] accepts. Agrees with
+/// [verify_batch_strict] about which entries are valid.
+pub fn verify_strict(
+    entry_bytes: &[u8],
+    payload: Option<&[u8]>,
+    lipmaa_link: Option<&[u8]>,
+    backlink: Option<&[u8]>,
+) -> Result<(), Error> {
+    verify_and_decode(entry_bytes, payload, lipmaa_link, backlink, true).map(|_| ())
+}
+
+/// Verify `entry_bytes` like [verify], rejecting entries that `policy` doesn't allow.
+///
+/// The policy is checked before the links, payload and signature, so unwanted entries are
+/// rejected cheaply. A [strict](VerifyPolicy::strict) policy checks the signature like
+/// [verify_strict].
+pub fn verify_with_policy<A, L>(
+    entry_bytes: &[u8],
+    payload: Option<&[u8]>,
+    lipmaa_link: Option<&[u8]>,
+    backlink: Option<&[u8]>,
+    policy: &VerifyPolicy<A, L>,
+) -> Result<(), Error>
+where
+    A: AsRef<[PublicKey]>,
+    L: AsRef<[RangeInclusive<u64>]>,
+{
     let entry = decode(entry_bytes).context(DecodeEntry)?;
+    policy.check(&entry)?;
+
+    let payload_algorithm = entry.payload_hash.algorithm();
+    let payload_hash_and_size =
+        payload.map(|payload| (payload_algorithm.hash(payload), payload.len() as u64));
+
+    verify_decoded(
+        &entry,
+        payload_hash_and_size,
+        lipmaa_link,
+        backlink,
+        policy.is_strict(),
+    )
+}
+
+/// Verify `entry_bytes` like [verify], returning a [VerifiedEntry] if it's valid.
+///
+/// The [VerifiedEntry] holds the decoded entry, the hash of its encoding and which checks ran, so
+/// the caller doesn't need to decode or hash the entry again. Any bytes after the entry in
+/// `entry_bytes` are left out of its bytes and hash, so the hash is the one later entries link
+/// to.
+pub fn verify_entry<'a>(
+    entry_bytes: &'a [u8],
+    payload: Option<&[u8]>,
+    lipmaa_link: Option<&[u8]>,
+    backlink: Option<&[u8]>,
+) -> Result<VerifiedEntry<'a>, Error> {
+    let (entry, length) = verify_and_decode(entry_bytes, payload, lipmaa_link, backlink, false)?;
+    let entry_bytes = &entry_bytes[..length];
+
+    let backlink_checked = backlink.is_some() && entry.backlink.is_some();
+    let lipmaa_link_checked = match entry.seq_num {
+        1 => false,
+        seq_num if is_lipmaa_required(seq_num) => lipmaa_link.is_some(),
+        _ => backlink_checked,
+    };
+    let checks = VerifiedChecks {
+        signature: true,
+        payload: payload.is_some(),
+        backlink: backlink_checked,
+        lipmaa_link: lipmaa_link_checked,
+    };
+
+    let hash = entry.payload_hash.algorithm().hash(entry_bytes);
 
-    let payload_and_hash = payload.map(|payload| (payload, new_blake2b(payload)));
-    let lipmaa_link_and_hash = lipmaa_link.map(|link| (link, new_blake2b(link)));
-    let backlink_and_hash = backlink.map(|link| (link, new_blake2b(link)));
+    Ok(VerifiedEntry {
+        entry,
+        bytes: entry_bytes,
+        hash,
+        checks,
+    })
+}
 
-    verify_links_and_payload(
+/// Verify `entry_bytes` like [verify], checking a claimed payload hash and size instead of the
+/// payload bytes.
+///
+/// Useful when the payload lives somewhere that already knows its hash and size. The claim is
+/// trusted, so this only proves the entry refers to a payload with that hash and size.
+pub fn verify_with_payload_hash<H: Borrow<[u8]>>(
+    entry_bytes: &[u8],
+    payload_hash: &YamfHash<H>,
+    payload_size: u64,
+    lipmaa_link: Option<&[u8]>,
+    backlink: Option<&[u8]>,
+) -> Result<(), Error> {
+    let entry = decode(entry_bytes).context(DecodeEntry)?;
+
+    verify_decoded(
         &entry,
-        payload_and_hash,
+        Some((payload_hash.as_borrowed(), payload_size)),
+        lipmaa_link,
+        backlink,
+        false,
+    )
+}
+
+/// Verify `entry_bytes` like [verify], reading the payload from `payload`.
+///
+/// The payload is hashed as it is read, so the whole payload never has to be in memory. Useful
+/// for big payloads like files.
+#[cfg(feature = "std")]
+pub fn verify_from_reader<R: Read>(
+    entry_bytes: &[u8],
+    payload: Option<R>,
+    lipmaa_link: Option<&[u8]>,
+    backlink: Option<&[u8]>,
+) -> Result<(), Error> {
+    let entry = decode(entry_bytes).context(DecodeEntry)?;
+
+    let payload_hash_and_size = payload
+        .map(|mut payload| {
+            let mut hasher = YamfHasher::new(entry.payload_hash.algorithm());
+            io::copy(&mut payload, &mut hasher).context(ReadPayload)?;
+            Ok((hasher.finalize(), hasher.bytes_hashed()))
+        })
+        .transpose()?;
+
+    verify_decoded(&entry, payload_hash_and_size, lipmaa_link, backlink, false)
+}
+
+fn verify_and_decode<'a>(
+    entry_bytes: &'a [u8],
+    payload: Option<&[u8]>,
+    lipmaa_link: Option<&[u8]>,
+    backlink: Option<&[u8]>,
+    strict: bool,
+) -> Result<EntryWithLength<'a>, Error> {
+    // Decode the entry that we want to verify.
+    let (entry, length) = decode_with_length(entry_bytes).context(DecodeEntry)?;
+
+    let payload_algorithm = entry.payload_hash.algorithm();
+    let payload_hash_and_size =
+        payload.map(|payload| (payload_algorithm.hash(payload), payload.len() as u64));
+
+    verify_decoded(&entry, payload_hash_and_size, lipmaa_link, backlink, strict)?;
+
+    Ok((entry, length))
+}
+
+fn verify_decoded<P: Borrow<[u8]>>(
+    entry: &Entry<&[u8], &[u8]>,
+    payload_hash_and_size: Option<(YamfHash<P>, u64)>,
+    lipmaa_link: Option<&[u8]>,
+    backlink: Option<&[u8]>,
+    strict: bool,
+) -> Result<(), Error> {
+    // Hash with whichever algorithm the entry used for each hash.
+    let algorithm_of =
+        |hash: Option<&YamfHash<&[u8]>>| hash.map(YamfHash::algorithm).unwrap_or_default();
+    let lipmaa_link_algorithm = algorithm_of(entry.lipmaa_link.as_ref());
+    let backlink_algorithm = algorithm_of(entry.backlink.as_ref());
+
+    let lipmaa_link_and_hash = lipmaa_link.map(|link| (link, lipmaa_link_algorithm.hash(link)));
+    let backlink_and_hash = backlink.map(|link| (link, backlink_algorithm.hash(link)));
+
+    verify_links_and_payload_hash(
+        entry,
+        payload_hash_and_size,
         lipmaa_link_and_hash,
         backlink_and_hash,
     )?;
 
-    entry.verify_signature()
+    if strict {
+        entry.verify_signature_strict()
+    } else {
+        entry.verify_signature()
+    }
 }
@@ -0,0 +1,201 @@
+//! Limits on which entries a caller is willing to accept, on top of them being valid.
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::ops::RangeInclusive;
+use ed25519_dalek::PublicKey;
+use snafu::ensure;
+
+use super::error::*;
+use crate::entry::Entry;
+
+/// A [VerifyPolicy] that owns its lists of authors and log ids, eg. to keep it in a struct.
+#[cfg(feature = "alloc")]
+pub type OwnedVerifyPolicy = VerifyPolicy<Vec<PublicKey>, Vec<RangeInclusive<u64>>>;
+
+/// Limits on the entries a verifier accepts, so a hostile peer can't fill a store with huge or
+/// unwanted feeds.
+///
+/// Everything is allowed by default. Each limit is checked against the decoded entry before any
+/// hashing or signature checking, and each has its own [Error] variant:
+///
+/// - `max_payload_size`: [PayloadTooLarge](Error::PayloadTooLarge)
+/// - `max_seq_num`: [SeqNumTooLarge](Error::SeqNumTooLarge)
+/// - `allowed_authors`: [AuthorNotAllowed](Error::AuthorNotAllowed)
+/// - `allowed_log_ids`: [LogIdNotAllowed](Error::LogIdNotAllowed)
+/// - `allow_end_of_feed`: [EndOfFeedNotAllowed](Error::EndOfFeedNotAllowed)
+///
+/// A policy can also make the verifiers that take it check signatures like
+/// [verify_strict](super::verify_strict), see [strict](VerifyPolicy::strict).
+///
+/// The authors and log id ranges can be any slice-like type, eg. an array for a policy known at
+/// compile time or a `Vec` for one loaded from config.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifyPolicy<A = &'static [PublicKey], L = &'static [RangeInclusive<u64>]> {
+    max_payload_size: Option<u64>,
+    max_seq_num: Option<u64>,
+    allowed_authors: Option<A>,
+    allowed_log_ids: Option<L>,
+    allow_end_of_feed: bool,
+    strict: bool,
+}
+
+impl Default for VerifyPolicy {
+    fn default() -> Self {
+        VerifyPolicy::new()
+    }
+}
+
+impl VerifyPolicy {
+    /// A policy that allows every valid entry.
+    pub fn new() -> VerifyPolicy {
+        VerifyPolicy {
+            max_payload_size: None,
+            max_seq_num: None,
+            allowed_authors: None,
+            allowed_log_ids: None,
+            allow_end_of_feed: true,
+            strict: false,
+        }
+    }
+}
+
+impl<A, L> VerifyPolicy<A, L> {
+    /// Reject entries whose payload is bigger than `max_payload_size` bytes.
+    pub fn max_payload_size(mut self, max_payload_size: u64) -> Self {
+        self.max_payload_size = Some(max_payload_size);
+        self
+    }
+
+    /// Reject entries with a seq num bigger than `max_seq_num`.
+    pub fn max_seq_num(mut self, max_seq_num: u64) -> Self {
+        self.max_seq_num = Some(max_seq_num);
+        self
+    }
+
+    /// Only accept entries by one of `allowed_authors`.
+    pub fn allowed_authors<B>(self, allowed_authors: B) -> VerifyPolicy<B, L>
+    where
+        B: AsRef<[PublicKey]>,
+    {
+        VerifyPolicy {
+            max_payload_size: self.max_payload_size,
+            max_seq_num: self.max_seq_num,
+            allowed_authors: Some(allowed_authors),
+            allowed_log_ids: self.allowed_log_ids,
+            allow_end_of_feed: self.allow_end_of_feed,
+            strict: self.strict,
+        }
+    }
+
+    /// Only accept entries with a log id in one of the `allowed_log_ids` ranges.
+    pub fn allowed_log_ids<M>(self, allowed_log_ids: M) -> VerifyPolicy<A, M>
+    where
+        M: AsRef<[RangeInclusive<u64>]>,
+    {
+        VerifyPolicy {
+            max_payload_size: self.max_payload_size,
+            max_seq_num: self.max_seq_num,
+            allowed_authors: self.allowed_authors,
+            allowed_log_ids: Some(allowed_log_ids),
+            allow_end_of_feed: self.allow_end_of_feed,
+            strict: self.strict,
+        }
+    }
+
+    /// Accept entries that end their feed. Defaults to `true`.
+    pub fn allow_end_of_feed(mut self, allow_end_of_feed: bool) -> Self {
+        self.allow_end_of_feed = allow_end_of_feed;
+        self
+    }
+
+    /// Reject weak author keys and malleable signatures like [verify_strict](super::verify_strict).
+    /// Defaults to `false`.
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Whether signatures are checked like [verify_strict](super::verify_strict).
+    pub fn is_strict(&self) -> bool {
+        self.strict
+    }
+}
+
+impl<A, L> VerifyPolicy<A, L>
+where
+    A: AsRef<[PublicKey]>,
+    L: AsRef<[RangeInclusive<u64>]>,
+{
+    /// Check `entry` against the limits of this policy.
+    ///
+    /// Only looks at the fields of `entry`, so it doesn't prove the entry is valid.
+    pub fn check<H, S>(&self, entry: &Entry<H, S>) -> Result<()>
+    where
+        H: Borrow<[u8]>,
+        S: Borrow<[u8]>,
+    {
+        if let Some(max) = self.max_payload_size {
+            ensure!(
+                entry.payload_size <= max,
+                PayloadTooLarge {
+                    size: entry.payload_size,
+                    max
+                }
+            );
+        }
+
+        if let Some(max) = self.max_seq_num {
+            ensure!(
+                entry.seq_num <= max,
+                SeqNumTooLarge {
+                    seq_num: entry.seq_num,
+                    max
+                }
+            );
+        }
+
+        if let Some(ref allowed_authors) = self.allowed_authors {
+            ensure!(
+                allowed_authors.as_ref().contains(&entry.author),
+                AuthorNotAllowed
+            );
+        }
+
+        if let Some(ref allowed_log_ids) = self.allowed_log_ids {
+            ensure!(
+                allowed_log_ids
+                    .as_ref()
+                    .iter()
+                    .any(|range| range.contains(&entry.log_id)),
+                LogIdNotAllowed {
+                    log_id: entry.log_id
+                }
+            );
+        }
+
+        ensure!(
+            self.allow_end_of_feed || !entry.is_end_of_feed,
+            EndOfFeedNotAllowed
+        );
+
+        Ok(())
+    }
+
+    /// Copy the authors and log id ranges into `Vec`s.
+    #[cfg(feature = "alloc")]
+    pub fn into_owned(self) -> OwnedVerifyPolicy {
+        VerifyPolicy {
+            max_payload_size: self.max_payload_size,
+            max_seq_num: self.max_seq_num,
+            allowed_authors: self
+                .allowed_authors
+                .map(|authors| authors.as_ref().to_vec()),
+            allowed_log_ids: self
+                .allowed_log_ids
+                .map(|log_ids| log_ids.as_ref().to_vec()),
+            allow_end_of_feed: self.allow_end_of_feed,
+            strict: self.strict,
+        }
+    }
+}
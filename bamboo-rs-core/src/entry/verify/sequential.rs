@@ -0,0 +1,155 @@
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::ops::RangeInclusive;
+use ed25519_dalek::PublicKey;
+use rand_core::{CryptoRng, RngCore};
+use snafu::{ensure, ResultExt};
+
+use super::batch_entry::{verify_batch_entry_links_and_payload, BatchEntry, FeedKey};
+use super::ed25519_batch::verify_signatures_with_rng;
+use super::error::*;
+use super::VerifyPolicy;
+use crate::entry::Entry;
+
+/// Batch verify a collection of entries from **any number of authors and log_ids**, on a single
+/// thread.
+///
+/// For targets without `std`, only needs the `alloc` feature. Entries are grouped by author and
+/// log_id and their links are checked within each group, then all the signatures are checked in
+/// one ed25519 batch. The batch's random coefficients are picked from `rng`, which must be a
+/// cryptographically secure random number generator.
+pub fn verify_batch_sequential<E, P, R>(
+    entries_and_payloads: &[(E, Option<P>)],
+    rng: &mut R,
+) -> Result<()>
+where
+    E: AsRef<[u8]>,
+    P: AsRef<[u8]>,
+    R: RngCore + CryptoRng,
+{
+    verify_batch_sequential_with(entries_and_payloads, None::<&VerifyPolicy>, false, rng)
+}
+
+/// Batch verify a collection of entries like [verify_batch_sequential], checking the signatures
+/// like [verify_strict](super::verify_strict).
+pub fn verify_batch_sequential_strict<E, P, R>(
+    entries_and_payloads: &[(E, Option<P>)],
+    rng: &mut R,
+) -> Result<()>
+where
+    E: AsRef<[u8]>,
+    P: AsRef<[u8]>,
+    R: RngCore + CryptoRng,
+{
+    verify_batch_sequential_with(entries_and_payloads, None::<&VerifyPolicy>, true, rng)
+}
+
+/// Batch verify a collection of entries like [verify_batch_sequential], rejecting entries that
+/// `policy` doesn't allow.
+///
+/// Every entry is checked against the policy before any links or signatures are. A
+/// [strict](VerifyPolicy::strict) policy checks the signatures like
+/// [verify_batch_sequential_strict].
+pub fn verify_batch_sequential_with_policy<E, P, A, L, R>(
+    entries_and_payloads: &[(E, Option<P>)],
+    policy: &VerifyPolicy<A, L>,
+    rng: &mut R,
+) -> Result<()>
+where
+    E: AsRef<[u8]>,
+    P: AsRef<[u8]>,
+    R: RngCore + CryptoRng,
+    A: AsRef<[PublicKey]>,
+    L: AsRef<[RangeInclusive<u64>]>,
+{
+    verify_batch_sequential_with(entries_and_payloads, Some(policy), policy.is_strict(), rng)
+}
+
+// Decode each entry once, checking it against `policy` if there is one, then verify the links
+// and signatures of the decoded entries.
+fn verify_batch_sequential_with<E, P, A, L, R>(
+    entries_and_payloads: &[(E, Option<P>)],
+    policy: Option<&VerifyPolicy<A, L>>,
+    strict: bool,
+    rng: &mut R,
+) -> Result<()>
+where
+    E: AsRef<[u8]>,
+    P: AsRef<[u8]>,
+    A: AsRef<[PublicKey]>,
+    L: AsRef<[RangeInclusive<u64>]>,
+    R: RngCore + CryptoRng,
+{
+    let batch_entries = entries_and_payloads
+        .iter()
+        .map(|(bytes, payload)| {
+            BatchEntry::new_with_policy(bytes.as_ref(), payload.as_ref(), policy)
+        })
+        .collect::<Result<Vec<_>>>()?;
+    verify_links_and_payload_sequential(&batch_entries)?;
+
+    let entries = batch_entries
+        .into_iter()
+        .map(|batch_entry| batch_entry.entry)
+        .collect::<Vec<_>>();
+    verify_signatures_with_rng(&entries, strict, rng)
+}
+
+/// Batch verify the links + payloads of a collection of entries from **any number of authors and
+/// log_ids**, on a single thread.
+pub fn verify_batch_links_and_payload_sequential<E: AsRef<[u8]>, P: AsRef<[u8]>>(
+    entries_and_payloads: &[(E, Option<P>)],
+) -> Result<()> {
+    let batch_entries = entries_and_payloads
+        .iter()
+        .map(|(bytes, payload)| BatchEntry::new(bytes.as_ref(), payload.as_ref()))
+        .collect::<Result<Vec<_>>>()?;
+    verify_links_and_payload_sequential(&batch_entries)
+}
+
+// Verify the links + payloads of decoded entries from any number of feeds.
+fn verify_links_and_payload_sequential(batch_entries: &[BatchEntry]) -> Result<()> {
+    // Group into logs by author and log_id, then by seq num within each log. Only one entry with
+    // a seq num could have its links and payload checked, so duplicates are an error.
+    let mut logs = BTreeMap::<FeedKey, BTreeMap<u64, &BatchEntry>>::new();
+    for batch_entry in batch_entries.iter() {
+        let log = logs.entry(batch_entry.feed_key()).or_default();
+        let seq_num = batch_entry.entry.seq_num;
+        ensure!(!log.contains_key(&seq_num), DuplicateSeqNum { seq_num });
+        log.insert(seq_num, batch_entry);
+    }
+
+    logs.values().try_for_each(|log| {
+        log.values().try_for_each(|batch_entry| {
+            verify_batch_entry_links_and_payload(batch_entry, |seq_num| log.get(&seq_num))
+        })
+    })
+}
+
+/// Batch verify the signatures of a collection of entries, on a single thread, picking the batch's
+/// random coefficients from `rng`.
+pub fn verify_batch_signatures_sequential<T, R>(entries_bytes: &[T], rng: &mut R) -> Result<()>
+where
+    T: AsRef<[u8]>,
+    R: RngCore + CryptoRng,
+{
+    verify_batch_signatures_sequential_with(entries_bytes, false, rng)
+}
+
+fn verify_batch_signatures_sequential_with<T, R>(
+    entries_bytes: &[T],
+    strict: bool,
+    rng: &mut R,
+) -> Result<()>
+where
+    T: AsRef<[u8]>,
+    R: RngCore + CryptoRng,
+{
+    let entries = entries_bytes
+        .iter()
+        .map(|bytes| Entry::try_from(bytes.as_ref()).context(DecodeEntry))
+        .collect::<Result<Vec<_>>>()?;
+
+    verify_signatures_with_rng(&entries, strict, rng)
+}
@@ -0,0 +1,44 @@
+//! Checks that make signature verification strict, ruling out ed25519 malleability.
+//!
+//! Honest keys and signatures are always points in the prime order subgroup. Rejecting anything
+//! else means a cofactorless check of a single signature and a batch check of many signatures
+//! accept exactly the same entries.
+use core::borrow::Borrow;
+use curve25519_dalek::edwards::CompressedEdwardsY;
+use ed25519_dalek::{PublicKey, PUBLIC_KEY_LENGTH};
+use snafu::{ensure, OptionExt};
+
+use super::error::*;
+use crate::entry::Entry;
+
+/// Is `key` a weak ed25519 key, one with a small order component that makes signatures by it
+/// malleable.
+pub fn is_weak_key(key: &PublicKey) -> bool {
+    !is_prime_order_point(key.as_bytes())
+}
+
+// Check the author key and the R part of the signature are points in the prime order subgroup.
+//
+// The s part of the signature is checked to be canonical when it's decoded for verifying.
+pub(crate) fn check_strict<H, S>(entry: &Entry<H, S>) -> Result<()>
+where
+    H: Borrow<[u8]>,
+    S: Borrow<[u8]>,
+{
+    ensure!(!is_weak_key(&entry.author), WeakAuthorKey);
+
+    let sig = entry.sig.as_ref().context(InvalidSignature)?.0.borrow();
+    ensure!(
+        sig.len() >= PUBLIC_KEY_LENGTH && is_prime_order_point(&sig[..PUBLIC_KEY_LENGTH]),
+        WeakSignature
+    );
+
+    Ok(())
+}
+
+fn is_prime_order_point(bytes: &[u8]) -> bool {
+    CompressedEdwardsY::from_slice(bytes)
+        .decompress()
+        .map(|point| !point.is_small_order() && point.is_torsion_free())
+        .unwrap_or(false)
+}
@@ -0,0 +1,63 @@
+use arrayvec::ArrayVec;
+
+use crate::entry::Entry;
+use crate::yamf_hash::{YamfHash, BLAKE2B_HASH_SIZE};
+
+/// Which checks [verify_entry](super::verify_entry) was able to run.
+///
+/// The signature is always checked. The others depend on which of the payload, lipmaa entry and
+/// backlink entry were provided.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash)]
+pub struct VerifiedChecks {
+    /// The signature is valid for the entry and its author.
+    pub signature: bool,
+    /// The payload matches the payload hash and size in the entry.
+    pub payload: bool,
+    /// The backlink entry hashes to the backlink in the entry and is from the same log.
+    pub backlink: bool,
+    /// The lipmaa entry hashes to the lipmaa link in the entry and is from the same log.
+    ///
+    /// Also set when the entry doesn't need a lipmaa link and the backlink was checked, because
+    /// then the lipmaa entry is the backlink entry.
+    pub lipmaa_link: bool,
+}
+
+/// An [Entry] that has been verified by [verify_entry](super::verify_entry).
+///
+/// Can only be made by verifying, so code that takes a `VerifiedEntry` can't be handed
+/// unverified data by mistake. Also holds the hash of the encoded entry, which is what the next
+/// entries in the log link to.
+#[derive(Debug, Eq, PartialEq)]
+pub struct VerifiedEntry<'a> {
+    pub(super) entry: Entry<&'a [u8], &'a [u8]>,
+    pub(super) bytes: &'a [u8],
+    pub(super) hash: YamfHash<ArrayVec<[u8; BLAKE2B_HASH_SIZE]>>,
+    pub(super) checks: VerifiedChecks,
+}
+
+impl<'a> VerifiedEntry<'a> {
+    /// The decoded entry.
+    pub fn entry(&self) -> &Entry<&'a [u8], &'a [u8]> {
+        &self.entry
+    }
+
+    /// The encoded bytes that were verified.
+    pub fn bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    /// The hash of the encoded entry, made with the same algorithm as the entry's payload hash.
+    pub fn hash(&self) -> &YamfHash<ArrayVec<[u8; BLAKE2B_HASH_SIZE]>> {
+        &self.hash
+    }
+
+    /// The checks that ran when this was verified.
+    pub fn checks(&self) -> VerifiedChecks {
+        self.checks
+    }
+
+    /// Consume this, returning the decoded entry.
+    pub fn into_entry(self) -> Entry<&'a [u8], &'a [u8]> {
+        self.entry
+    }
+}
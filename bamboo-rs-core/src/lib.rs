@@ -57,21 +57,32 @@
 //! ```
 #![cfg_attr(not(feature = "std"), no_std)]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
 #[macro_use]
 extern crate serde_derive;
 #[macro_use]
 extern crate static_assertions;
 
+#[cfg(feature = "arbitrary")]
+pub mod arbitrary;
+pub mod certificate_pool;
+#[cfg(feature = "cid")]
+pub mod cid;
 pub mod entry;
 pub mod signature;
+pub mod uri;
 pub mod yamf_hash;
 
 mod util;
 
-pub use crate::yamf_hash::{YamfHash, BLAKE2B_HASH_SIZE, OUTBYTES};
+pub use crate::certificate_pool::{certificate_pool, lipmaa_path};
+pub use crate::yamf_hash::{HashAlgorithm, YamfHash, YamfHasher, BLAKE2B_HASH_SIZE, OUTBYTES};
 pub use ed25519_dalek::{Keypair, PublicKey, SecretKey, SignatureError};
 #[cfg(feature = "std")]
 pub use entry::verify::verify_batch;
 pub use entry::{decode, publish, verify, Entry};
 pub use lipmaa_link::lipmaa;
 pub use signature::{Signature, ED25519_SIGNATURE_SIZE};
+pub use uri::{Author, BambooUri};
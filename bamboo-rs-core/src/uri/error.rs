@@ -0,0 +1,24 @@
+use snafu::Snafu;
+
+#[derive(Debug, Clone, Snafu)]
+#[snafu(visibility = "pub(super)")]
+pub enum Error {
+    #[snafu(display("Author is not valid hex or is not {} bytes", length))]
+    DecodeAuthorHex { length: usize },
+    #[snafu(display("Author is not a valid ed25519 public key"))]
+    InvalidAuthor,
+    #[snafu(display("Bamboo uri must start with `bamboo:`"))]
+    MissingScheme,
+    #[snafu(display("Bamboo uri must have a log id"))]
+    MissingLogId,
+    #[snafu(display("Bamboo uri log id is not a u64"))]
+    InvalidLogId,
+    #[snafu(display("Bamboo uri seq num is not a u64"))]
+    InvalidSeqNum,
+    #[snafu(display("Sequence numbers start at 1 but 0 was used"))]
+    ZeroSeqNum,
+    #[snafu(display("Bamboo uri has too many segments, expected at most author/log_id/seq_num"))]
+    TooManySegments,
+}
+
+pub type Result<T, E = Error> = core::result::Result<T, E>;
@@ -0,0 +1,189 @@
+//! Text forms of authors and logs, for passing feed references through config files, logs and
+//! URLs.
+//!
+//! An [Author] is written as the lower case hex of its public key. A [BambooUri] names a log, or
+//! an entry in one:
+//!
+//! ```text
+//! bamboo:<author>/<log_id>
+//! bamboo:<author>/<log_id>/<seq_num>
+//! ```
+//!
+//! [YamfHash](crate::YamfHash) and [Entry] are written as hex too, see their
+//! `Display` impls.
+use core::borrow::Borrow;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::str::FromStr;
+use ed25519_dalek::{PublicKey, PUBLIC_KEY_LENGTH};
+use snafu::{ensure, NoneError, OptionExt, ResultExt};
+
+use crate::entry::Entry;
+use crate::util::write_hex;
+
+pub mod error;
+pub use error::*;
+
+pub const BAMBOO_URI_SCHEME: &str = "bamboo:";
+
+/// The public key of a log's author, with a hex text form.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Author(pub PublicKey);
+
+impl Hash for Author {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.as_bytes().hash(state);
+    }
+}
+
+impl From<PublicKey> for Author {
+    fn from(public_key: PublicKey) -> Author {
+        Author(public_key)
+    }
+}
+
+impl From<Author> for PublicKey {
+    fn from(author: Author) -> PublicKey {
+        author.0
+    }
+}
+
+impl fmt::Display for Author {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_hex(f, self.0.as_bytes())
+    }
+}
+
+impl FromStr for Author {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Author> {
+        let mut bytes = [0u8; PUBLIC_KEY_LENGTH];
+        hex::decode_to_slice(s, &mut bytes)
+            .map_err(|_| NoneError)
+            .context(DecodeAuthorHex {
+                length: PUBLIC_KEY_LENGTH,
+            })?;
+
+        let public_key = PublicKey::from_bytes(&bytes)
+            .map_err(|_| NoneError)
+            .context(InvalidAuthor)?;
+
+        Ok(Author(public_key))
+    }
+}
+
+/// A reference to a log, or to an entry in a log when `seq_num` is set.
+///
+/// Formats as `bamboo:<author>/<log_id>` or `bamboo:<author>/<log_id>/<seq_num>`, with the author
+/// in hex and the numbers in decimal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BambooUri {
+    pub author: PublicKey,
+    pub log_id: u64,
+    pub seq_num: Option<u64>,
+}
+
+impl BambooUri {
+    /// A uri for the log `log_id` of `author`.
+    pub fn log(author: PublicKey, log_id: u64) -> BambooUri {
+        BambooUri {
+            author,
+            log_id,
+            seq_num: None,
+        }
+    }
+
+    /// A uri for the entry `seq_num` in the log `log_id` of `author`.
+    pub fn entry(author: PublicKey, log_id: u64, seq_num: u64) -> BambooUri {
+        BambooUri {
+            author,
+            log_id,
+            seq_num: Some(seq_num),
+        }
+    }
+
+    /// The uri of the log this uri's entry is in, or this uri if it's already a log.
+    pub fn as_log(&self) -> BambooUri {
+        BambooUri::log(self.author, self.log_id)
+    }
+}
+
+impl Hash for BambooUri {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.author.as_bytes().hash(state);
+        self.log_id.hash(state);
+        self.seq_num.hash(state);
+    }
+}
+
+impl<'a, H, S> From<&'a Entry<H, S>> for BambooUri
+where
+    H: Borrow<[u8]>,
+    S: Borrow<[u8]>,
+{
+    fn from(entry: &'a Entry<H, S>) -> BambooUri {
+        BambooUri::entry(entry.author, entry.log_id, entry.seq_num)
+    }
+}
+
+impl fmt::Display for BambooUri {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}{}/{}",
+            BAMBOO_URI_SCHEME,
+            Author(self.author),
+            self.log_id
+        )?;
+
+        match self.seq_num {
+            Some(seq_num) => write!(f, "/{}", seq_num),
+            None => Ok(()),
+        }
+    }
+}
+
+impl FromStr for BambooUri {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<BambooUri> {
+        let path = s.strip_prefix(BAMBOO_URI_SCHEME).context(MissingScheme)?;
+        let mut segments = path.split('/');
+
+        // `split` always yields at least one segment.
+        let Author(author) = segments.next().unwrap_or_default().parse()?;
+
+        let log_id = segments
+            .next()
+            .context(MissingLogId)
+            .and_then(|log_id| parse_decimal(log_id).context(InvalidLogId))?;
+
+        let seq_num = match segments.next() {
+            Some(seq_num) => {
+                let seq_num = parse_decimal(seq_num).context(InvalidSeqNum)?;
+                ensure!(seq_num > 0, ZeroSeqNum);
+                Some(seq_num)
+            }
+            None => None,
+        };
+
+        ensure!(segments.next().is_none(), TooManySegments);
+
+        Ok(BambooUri {
+            author,
+            log_id,
+            seq_num,
+        })
+    }
+}
+
+// Parse a u64 written in decimal digits. `u64::from_str` also accepts a leading `+`, which
+// `Display` never writes.
+fn parse_decimal(s: &str) -> Option<u64> {
+    if s.is_empty() || !s.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+
+    s.parse().ok()
+}
@@ -1,2 +1,9 @@
+use core::fmt;
+
 #[cfg(feature = "std")]
 pub mod hex_serde;
+
+/// Write `bytes` as lower case hex without allocating.
+pub(crate) fn write_hex(f: &mut fmt::Formatter, bytes: &[u8]) -> fmt::Result {
+    bytes.iter().try_for_each(|byte| write!(f, "{:02x}", byte))
+}
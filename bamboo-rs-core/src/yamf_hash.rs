@@ -1 +0,0 @@
-pub use yamf_hash::*;
@@ -0,0 +1,14 @@
+use snafu::Snafu;
+
+#[derive(Debug, Clone, Snafu)]
+#[snafu(visibility = "pub(crate)")]
+pub enum Error {
+    EncodeError,
+    EncodeWriteError,
+    DecodeVaru64Error,
+    DecodeError,
+    DecodeLengthError,
+    DecodeHexError,
+}
+
+pub type Result<T, E = Error> = core::result::Result<T, E>;
@@ -0,0 +1,391 @@
+//! Encode and decode [yamf-hashes](https://github.com/AljoschaMeyer/yamf-hash)
+//!
+//! BLAKE2b is always available. BLAKE3 is available with the `blake3` feature.
+
+#[cfg(feature = "std")]
+use crate::util::hex_serde::{hex_from_bytes, vec_from_hex};
+use crate::util::write_hex;
+use arrayvec::ArrayVec;
+use blake2b_simd::blake2b;
+use core::borrow::Borrow;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::iter::FromIterator;
+use core::str::FromStr;
+
+#[cfg(feature = "std")]
+use std::io::Write;
+
+use varu64::{decode as varu64_decode, encode as varu64_encode, encoding_length};
+
+pub mod error;
+pub use error::*;
+
+pub use blake2b_simd::OUTBYTES;
+pub const BLAKE2B_HASH_SIZE: usize = 64;
+// This is a way to hard code a value that cbindgen can use, but make sure at compile time
+// that the value is actually correct.
+const_assert_eq!(blake2b_hash_size; BLAKE2B_HASH_SIZE, OUTBYTES);
+
+pub const BLAKE2B_NUMERIC_ID: u64 = 0;
+
+pub const BLAKE3_HASH_SIZE: usize = 32;
+#[cfg(feature = "blake3")]
+const_assert_eq!(blake3_hash_size; BLAKE3_HASH_SIZE, blake3::OUT_LEN);
+
+pub const BLAKE3_NUMERIC_ID: u64 = 1;
+
+/// Payloads at least this big are hashed with BLAKE3 on multiple threads.
+#[cfg(all(feature = "blake3", feature = "std"))]
+pub const BLAKE3_RAYON_THRESHOLD: usize = 128 * 1024;
+
+/// The maximum number of bytes this will use for any variant.
+///
+/// This is a bit yuck because it knows the number of bytes varu64 uses to encode the
+/// BLAKE2B_HASH_SIZE and the BLAKE2B_NUMERIC_ID (2).
+/// This is unlikely to cause a problem until there are hundreds of variants.
+pub const MAX_YAMF_HASH_SIZE: usize = BLAKE2B_HASH_SIZE + 2;
+
+/// An owned [YamfHash] decoded by [YamfHash::decode_owned], and the bytes after it.
+pub type DecodedOwnedYamfHash<'a> = (YamfHash<ArrayVec<[u8; BLAKE2B_HASH_SIZE]>>, &'a [u8]);
+
+/// Variants of `YamfHash`
+#[derive(Deserialize, Serialize, Debug, Eq, Clone)]
+pub enum YamfHash<T: Borrow<[u8]>> {
+    #[cfg_attr(
+        feature = "std",
+        serde(serialize_with = "hex_from_bytes", deserialize_with = "vec_from_hex")
+    )]
+    #[cfg_attr(feature = "std", serde(bound(deserialize = "T: From<Vec<u8>>")))]
+    Blake2b(T),
+    #[cfg(feature = "blake3")]
+    #[cfg_attr(
+        feature = "std",
+        serde(serialize_with = "hex_from_bytes", deserialize_with = "vec_from_hex")
+    )]
+    #[cfg_attr(feature = "std", serde(bound(deserialize = "T: From<Vec<u8>>")))]
+    Blake3(T),
+}
+
+/// The hash algorithms a [YamfHash] can be made with.
+///
+/// Use this to choose which algorithm a log uses when publishing.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum HashAlgorithm {
+    #[default]
+    Blake2b,
+    #[cfg(feature = "blake3")]
+    Blake3,
+}
+
+impl HashAlgorithm {
+    /// Hash `bytes` with this algorithm.
+    pub fn hash(self, bytes: &[u8]) -> YamfHash<ArrayVec<[u8; BLAKE2B_HASH_SIZE]>> {
+        match self {
+            HashAlgorithm::Blake2b => new_blake2b(bytes),
+            #[cfg(feature = "blake3")]
+            HashAlgorithm::Blake3 => new_blake3(bytes),
+        }
+    }
+
+    /// The yamf numeric id of this algorithm.
+    pub fn numeric_id(self) -> u64 {
+        match self {
+            HashAlgorithm::Blake2b => BLAKE2B_NUMERIC_ID,
+            #[cfg(feature = "blake3")]
+            HashAlgorithm::Blake3 => BLAKE3_NUMERIC_ID,
+        }
+    }
+
+    /// The number of bytes in a hash made by this algorithm.
+    pub fn hash_size(self) -> usize {
+        match self {
+            HashAlgorithm::Blake2b => BLAKE2B_HASH_SIZE,
+            #[cfg(feature = "blake3")]
+            HashAlgorithm::Blake3 => BLAKE3_HASH_SIZE,
+        }
+    }
+
+    /// Wrap an already computed digest as a [YamfHash] of this algorithm.
+    pub fn with_bytes<T: Borrow<[u8]>>(self, bytes: T) -> YamfHash<T> {
+        match self {
+            HashAlgorithm::Blake2b => YamfHash::Blake2b(bytes),
+            #[cfg(feature = "blake3")]
+            HashAlgorithm::Blake3 => YamfHash::Blake3(bytes),
+        }
+    }
+
+    /// The algorithm for a yamf numeric id, if it's supported.
+    pub fn from_numeric_id(id: u64) -> Option<HashAlgorithm> {
+        match id {
+            BLAKE2B_NUMERIC_ID => Some(HashAlgorithm::Blake2b),
+            #[cfg(feature = "blake3")]
+            BLAKE3_NUMERIC_ID => Some(HashAlgorithm::Blake3),
+            _ => None,
+        }
+    }
+}
+
+impl<B1: Borrow<[u8]>, B2: Borrow<[u8]>> PartialEq<YamfHash<B1>> for YamfHash<B2> {
+    fn eq(&self, other: &YamfHash<B1>) -> bool {
+        self.algorithm() == other.algorithm() && self.as_bytes() == other.as_bytes()
+    }
+}
+
+impl<T: Borrow<[u8]>> Hash for YamfHash<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        Hash::hash(&self.algorithm(), state);
+        self.as_bytes().hash(state);
+    }
+}
+
+/// Formats the yamf encoding of the hash, including the algorithm, as lower case hex.
+impl<T: Borrow<[u8]>> fmt::Display for YamfHash<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut out = [0u8; MAX_YAMF_HASH_SIZE];
+        let size = self.encode(&mut out).map_err(|_| fmt::Error)?;
+        write_hex(f, &out[..size])
+    }
+}
+
+/// Parses the hex encoding written by `Display`.
+impl FromStr for YamfHash<ArrayVec<[u8; BLAKE2B_HASH_SIZE]>> {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        let mut bytes = [0u8; MAX_YAMF_HASH_SIZE];
+        let size = s.len() / 2;
+
+        if size > MAX_YAMF_HASH_SIZE {
+            return Err(Error::DecodeError);
+        }
+
+        hex::decode_to_slice(s, &mut bytes[..size]).map_err(|_| Error::DecodeHexError)?;
+
+        match YamfHash::<&[u8]>::decode_owned(&bytes[..size])? {
+            (hash, []) => Ok(hash),
+            _ => Err(Error::DecodeError),
+        }
+    }
+}
+
+pub fn new_blake2b(bytes: &[u8]) -> YamfHash<ArrayVec<[u8; BLAKE2B_HASH_SIZE]>> {
+    let hash_bytes = blake2b(bytes);
+
+    let vec_bytes: ArrayVec<[u8; BLAKE2B_HASH_SIZE]> =
+        ArrayVec::from_iter(hash_bytes.as_bytes().iter().copied());
+
+    YamfHash::Blake2b(vec_bytes)
+}
+
+/// Hash `bytes` with BLAKE3.
+///
+/// The hash is stored in the same sized `ArrayVec` as [new_blake2b] uses so either can be used
+/// wherever an owned hash is expected. With the `std` feature, inputs (or chunks passed to
+/// [YamfHasher::update]) of at least [BLAKE3_RAYON_THRESHOLD] bytes are hashed in parallel using
+/// rayon.
+#[cfg(feature = "blake3")]
+pub fn new_blake3(bytes: &[u8]) -> YamfHash<ArrayVec<[u8; BLAKE2B_HASH_SIZE]>> {
+    let mut hasher = YamfHasher::new(HashAlgorithm::Blake3);
+    hasher.update(bytes);
+    hasher.finalize()
+}
+
+/// Hashes bytes that arrive in chunks, eg. a payload that is too big to hold in memory.
+///
+/// Also counts the bytes it was given, which is the payload size of an entry. Implements
+/// `std::io::Write` with the `std` feature so a reader can be hashed with `std::io::copy`.
+#[derive(Debug, Clone)]
+pub struct YamfHasher {
+    state: HasherState,
+    bytes_hashed: u64,
+}
+
+// Hashers are short lived and no_std can't box, so the size difference is fine.
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone)]
+enum HasherState {
+    Blake2b(blake2b_simd::State),
+    #[cfg(feature = "blake3")]
+    Blake3(blake3::Hasher),
+}
+
+impl YamfHasher {
+    pub fn new(algorithm: HashAlgorithm) -> YamfHasher {
+        let state = match algorithm {
+            HashAlgorithm::Blake2b => HasherState::Blake2b(blake2b_simd::State::new()),
+            #[cfg(feature = "blake3")]
+            HashAlgorithm::Blake3 => HasherState::Blake3(blake3::Hasher::new()),
+        };
+
+        YamfHasher {
+            state,
+            bytes_hashed: 0,
+        }
+    }
+
+    /// Add `bytes` to the hash.
+    pub fn update(&mut self, bytes: &[u8]) -> &mut Self {
+        match self.state {
+            HasherState::Blake2b(ref mut state) => {
+                state.update(bytes);
+            }
+            #[cfg(all(feature = "blake3", feature = "std"))]
+            HasherState::Blake3(ref mut hasher) if bytes.len() >= BLAKE3_RAYON_THRESHOLD => {
+                hasher.update_rayon(bytes);
+            }
+            #[cfg(feature = "blake3")]
+            HasherState::Blake3(ref mut hasher) => {
+                hasher.update(bytes);
+            }
+        }
+        self.bytes_hashed += bytes.len() as u64;
+        self
+    }
+
+    /// The hash of all the bytes added so far.
+    pub fn finalize(&self) -> YamfHash<ArrayVec<[u8; BLAKE2B_HASH_SIZE]>> {
+        match self.state {
+            HasherState::Blake2b(ref state) => state.finalize().into(),
+            #[cfg(feature = "blake3")]
+            HasherState::Blake3(ref hasher) => hasher.finalize().into(),
+        }
+    }
+
+    /// The number of bytes added so far.
+    pub fn bytes_hashed(&self) -> u64 {
+        self.bytes_hashed
+    }
+
+    pub fn algorithm(&self) -> HashAlgorithm {
+        match self.state {
+            HasherState::Blake2b(_) => HashAlgorithm::Blake2b,
+            #[cfg(feature = "blake3")]
+            HasherState::Blake3(_) => HashAlgorithm::Blake3,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Write for YamfHasher {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a> From<&'a YamfHash<ArrayVec<[u8; BLAKE2B_HASH_SIZE]>>> for YamfHash<&'a [u8]> {
+    fn from(hash: &YamfHash<ArrayVec<[u8; BLAKE2B_HASH_SIZE]>>) -> YamfHash<&[u8]> {
+        hash.as_borrowed()
+    }
+}
+
+impl From<blake2b_simd::Hash> for YamfHash<ArrayVec<[u8; BLAKE2B_HASH_SIZE]>> {
+    fn from(hash: blake2b_simd::Hash) -> Self {
+        let vec_bytes: ArrayVec<[u8; BLAKE2B_HASH_SIZE]> =
+            ArrayVec::from_iter(hash.as_bytes().iter().copied());
+
+        YamfHash::Blake2b(vec_bytes)
+    }
+}
+
+#[cfg(feature = "blake3")]
+impl From<blake3::Hash> for YamfHash<ArrayVec<[u8; BLAKE2B_HASH_SIZE]>> {
+    fn from(hash: blake3::Hash) -> Self {
+        let vec_bytes: ArrayVec<[u8; BLAKE2B_HASH_SIZE]> =
+            ArrayVec::from_iter(hash.as_bytes().iter().copied());
+
+        YamfHash::Blake3(vec_bytes)
+    }
+}
+
+impl<T: Borrow<[u8]>> YamfHash<T> {
+    /// The algorithm this hash was made with.
+    pub fn algorithm(&self) -> HashAlgorithm {
+        match self {
+            YamfHash::Blake2b(_) => HashAlgorithm::Blake2b,
+            #[cfg(feature = "blake3")]
+            YamfHash::Blake3(_) => HashAlgorithm::Blake3,
+        }
+    }
+
+    /// The hash digest, without the yamf header.
+    pub fn as_bytes(&self) -> &[u8] {
+        match self {
+            YamfHash::Blake2b(bytes) => bytes.borrow(),
+            #[cfg(feature = "blake3")]
+            YamfHash::Blake3(bytes) => bytes.borrow(),
+        }
+    }
+
+    /// Borrow the digest of this hash.
+    pub fn as_borrowed(&self) -> YamfHash<&[u8]> {
+        self.algorithm().with_bytes(self.as_bytes())
+    }
+
+    /// Encode a YamfHash into the out buffer.
+    pub fn encode(&self, out: &mut [u8]) -> Result<usize, Error> {
+        let encoded_size = self.encoding_length();
+
+        if out.len() < encoded_size {
+            return Err(Error::EncodeError);
+        }
+
+        let algorithm = self.algorithm();
+        varu64_encode(algorithm.numeric_id(), &mut out[0..1]);
+        varu64_encode(algorithm.hash_size() as u64, &mut out[1..2]);
+        out[2..encoded_size].copy_from_slice(self.as_bytes());
+        Ok(encoded_size)
+    }
+
+    pub fn encoding_length(&self) -> usize {
+        let algorithm = self.algorithm();
+        encoding_length(algorithm.numeric_id())
+            + encoding_length(algorithm.hash_size() as u64)
+            + algorithm.hash_size()
+    }
+
+    /// Decode the `bytes` as a `YamfHash`
+    ///
+    /// The length in the yamf header must be the hash size of the algorithm.
+    pub fn decode(bytes: &[u8]) -> Result<(YamfHash<&[u8]>, &[u8]), Error> {
+        let (id, remaining_bytes) = varu64_decode(bytes).map_err(|_| Error::DecodeVaru64Error)?;
+        let algorithm = HashAlgorithm::from_numeric_id(id).ok_or(Error::DecodeError)?;
+
+        let (length, remaining_bytes) =
+            varu64_decode(remaining_bytes).map_err(|_| Error::DecodeVaru64Error)?;
+        if length != algorithm.hash_size() as u64 {
+            return Err(Error::DecodeLengthError);
+        }
+        if remaining_bytes.len() < algorithm.hash_size() {
+            return Err(Error::DecodeError);
+        }
+
+        let (hash_bytes, remaining_bytes) = remaining_bytes.split_at(algorithm.hash_size());
+        Ok((algorithm.with_bytes(hash_bytes), remaining_bytes))
+    }
+
+    /// Decode the `bytes` as a `YamfHash`
+    pub fn decode_owned(bytes: &[u8]) -> Result<DecodedOwnedYamfHash<'_>, Error> {
+        let (hash, remaining_bytes) = YamfHash::<&[u8]>::decode(bytes)?;
+        let vec_bytes = ArrayVec::from_iter(hash.as_bytes().iter().copied());
+        Ok((hash.algorithm().with_bytes(vec_bytes), remaining_bytes))
+    }
+
+    /// Encode a YamfHash into the writer.
+    #[cfg(feature = "std")]
+    pub fn encode_write<W: Write>(&self, mut w: W) -> Result<(), Error> {
+        let mut out = [0; 2];
+        let algorithm = self.algorithm();
+        varu64_encode(algorithm.numeric_id(), &mut out[0..1]);
+        varu64_encode(algorithm.hash_size() as u64, &mut out[1..2]);
+        w.write_all(&out).map_err(|_| Error::EncodeWriteError)?;
+        w.write_all(self.as_bytes())
+            .map_err(|_| Error::EncodeWriteError)?;
+        Ok(())
+    }
+}
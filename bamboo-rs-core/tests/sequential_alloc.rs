@@ -0,0 +1,69 @@
+//! The sequential batch verifier, which is the batch verifier available without `std`.
+//!
+//! Run against the `alloc` only build with:
+//! `cargo test --no-default-features --features alloc,u64_backend --test sequential_alloc`
+use bamboo_rs_core::entry::verify::sequential::{
+    verify_batch_sequential, verify_batch_signatures_sequential,
+};
+use bamboo_rs_core::entry::verify::Error as VerifyError;
+use bamboo_rs_core::entry::MAX_ENTRY_SIZE;
+use bamboo_rs_core::{lipmaa, publish, Keypair, ED25519_SIGNATURE_SIZE};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+const PAYLOAD: &[u8] = b"hello bamboo!";
+
+fn publish_entries(key_pair: &Keypair, count: u64) -> Vec<Vec<u8>> {
+    let mut entries: Vec<Vec<u8>> = Vec::new();
+
+    for seq_num in 1..=count {
+        let mut out = [0u8; MAX_ENTRY_SIZE];
+        let previous_seq_num = if seq_num == 1 {
+            None
+        } else {
+            Some(seq_num - 1)
+        };
+        let lipmaa_entry =
+            previous_seq_num.map(|_| entries[lipmaa(seq_num) as usize - 1].as_slice());
+        let backlink = previous_seq_num.map(|_| entries[seq_num as usize - 2].as_slice());
+
+        let size = publish(
+            &mut out,
+            key_pair,
+            0,
+            PAYLOAD,
+            false,
+            previous_seq_num,
+            lipmaa_entry,
+            backlink,
+        )
+        .unwrap();
+        entries.push(out[..size].to_vec());
+    }
+
+    entries
+}
+
+#[test]
+fn verify_batch_sequential_rejects_a_bad_signature_in_the_batch() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let key_pair = Keypair::generate(&mut rng);
+
+    let mut entries = publish_entries(&key_pair, 8);
+    let entries_and_payloads = entries
+        .iter()
+        .map(|bytes| (bytes.clone(), Some(PAYLOAD)))
+        .collect::<Vec<_>>();
+
+    verify_batch_sequential(&entries_and_payloads, &mut rng).unwrap();
+    verify_batch_signatures_sequential(&entries, &mut rng).unwrap();
+
+    // Flip a bit of the s part of one signature, so it still decodes but no longer verifies.
+    let s_start = entries[5].len() - ED25519_SIGNATURE_SIZE / 2;
+    entries[5][s_start] ^= 1;
+
+    match verify_batch_signatures_sequential(&entries, &mut rng) {
+        Err(VerifyError::InvalidSignature) => {}
+        other => panic!("{:?}", other),
+    }
+}
@@ -0,0 +1,589 @@
+use super::*;
+use bamboo_rs_core::entry::MAX_ENTRY_SIZE;
+use crc32fast::Hasher as Crc32;
+use fs2::FileExt;
+use memmap2::Mmap;
+use snafu::{ensure, ResultExt, Snafu};
+use std::collections::BTreeMap;
+use std::convert::TryInto;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+/// The size a segment file grows to before a new one is started, unless set with
+/// [FileEntryStore::open_with_max_segment_size].
+pub const DEFAULT_MAX_SEGMENT_SIZE: u64 = 16 * 1024 * 1024;
+
+const SEGMENT_EXTENSION: &str = "segment";
+const LOCK_FILE_NAME: &str = "LOCK";
+// Each record is the seq num, the entry length and a CRC-32 of those and the entry, all little
+// endian, then the entry.
+const RECORD_HEADER_SIZE: usize = 8 + 4 + 4;
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("Could not create log directory {}: {}", path.display(), source))]
+    CreateDirectory { path: PathBuf, source: io::Error },
+    #[snafu(display("Could not read log directory {}: {}", path.display(), source))]
+    ReadDirectory { path: PathBuf, source: io::Error },
+    #[snafu(display("Could not lock log directory {}, is it already open? {}", path.display(), source))]
+    LockDirectory { path: PathBuf, source: io::Error },
+    #[snafu(display("Could not open segment {}: {}", path.display(), source))]
+    OpenSegment { path: PathBuf, source: io::Error },
+    #[snafu(display("Could not read segment {}: {}", path.display(), source))]
+    ReadSegment { path: PathBuf, source: io::Error },
+    #[snafu(display("Could not map segment {}: {}", path.display(), source))]
+    MapSegment { path: PathBuf, source: io::Error },
+    #[snafu(display("Segment {} is corrupt at offset {}", path.display(), offset))]
+    CorruptSegment { path: PathBuf, offset: usize },
+    #[snafu(display("Could not truncate segment {}: {}", path.display(), source))]
+    TruncateSegment { path: PathBuf, source: io::Error },
+    #[snafu(display("Could not append to segment {}: {}", path.display(), source))]
+    AppendEntry { path: PathBuf, source: io::Error },
+    #[snafu(display("Could not sync segment {}: {}", path.display(), source))]
+    SyncSegment { path: PathBuf, source: io::Error },
+    #[snafu(display("Entry of {} bytes is larger than the maximum entry size", size))]
+    EntryTooLarge { size: usize },
+    #[snafu(display("Entries can't have a seq num of 0"))]
+    ZeroSeqNum,
+}
+
+pub type Result<T, E = Error> = core::result::Result<T, E>;
+
+/// An [EntryStore] that appends entries to segment files in a directory.
+///
+/// Each entry is appended as a record to the newest segment. A new segment is started once the
+/// newest one would grow past the max segment size. Older segments are memory mapped, and the
+/// newest one is also kept in memory, so [get_entry_ref](EntryStore::get_entry_ref) doesn't copy.
+///
+/// The seq num to offset index is rebuilt from the segments by [open](FileEntryStore::open). Each
+/// record has a checksum, and the newest segment is truncated at the first record that's half
+/// written or doesn't match its checksum, eg. after a crash.
+///
+/// Adding an entry that's already stored at its seq num does nothing. Adding different bytes at a
+/// seq num that's already in the store appends them, and the newest record wins, like
+/// [MemoryEntryStore] replacing it.
+///
+/// The directory is locked while the store is open, so a second store can't open it and append
+/// to the same segments. The segments must not be changed by anything else either.
+pub struct FileEntryStore {
+    dir: PathBuf,
+    // Holds the advisory lock on the directory until the store is dropped.
+    _lock: File,
+    max_segment_size: u64,
+    sealed: Vec<Mmap>,
+    active: File,
+    active_path: PathBuf,
+    active_number: u64,
+    active_bytes: Vec<u8>,
+    index: BTreeMap<u64, Location>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Location {
+    segment: usize,
+    offset: usize,
+    len: usize,
+}
+
+impl FileEntryStore {
+    /// Open the log in `dir`, creating the directory if it doesn't exist.
+    pub fn open<P: AsRef<Path>>(dir: P) -> Result<FileEntryStore> {
+        FileEntryStore::open_with_max_segment_size(dir, DEFAULT_MAX_SEGMENT_SIZE)
+    }
+
+    /// Open the log in `dir` like [open](FileEntryStore::open), starting a new segment whenever
+    /// the newest one would grow past `max_segment_size` bytes.
+    pub fn open_with_max_segment_size<P: AsRef<Path>>(
+        dir: P,
+        max_segment_size: u64,
+    ) -> Result<FileEntryStore> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir).context(CreateDirectory { path: &dir })?;
+        let lock = lock_dir(&dir)?;
+
+        let mut numbers = segment_numbers(&dir)?;
+        let active_number = numbers.pop().unwrap_or(0);
+
+        let mut index = BTreeMap::new();
+        let mut sealed = Vec::with_capacity(numbers.len());
+
+        for number in numbers {
+            let path = segment_path(&dir, number);
+            let file = File::open(&path).context(OpenSegment { path: &path })?;
+            // Safe as long as nothing else changes the segment while it's mapped.
+            let mmap = unsafe { Mmap::map(&file) }.context(MapSegment { path: &path })?;
+
+            let valid_len = index_segment(&mmap, sealed.len(), &mut index);
+            ensure!(
+                valid_len == mmap.len(),
+                CorruptSegment {
+                    path: &path,
+                    offset: valid_len
+                }
+            );
+
+            sealed.push(mmap);
+        }
+
+        let active_path = segment_path(&dir, active_number);
+        let mut active = open_active(&active_path)?;
+
+        let mut active_bytes = Vec::new();
+        active
+            .read_to_end(&mut active_bytes)
+            .context(ReadSegment { path: &active_path })?;
+
+        // Anything after the last whole record was cut off part way through being written, or was
+        // damaged after.
+        let valid_len = index_segment(&active_bytes, sealed.len(), &mut index);
+        if valid_len < active_bytes.len() {
+            active
+                .set_len(valid_len as u64)
+                .context(TruncateSegment { path: &active_path })?;
+            active_bytes.truncate(valid_len);
+        }
+
+        Ok(FileEntryStore {
+            dir,
+            _lock: lock,
+            max_segment_size,
+            sealed,
+            active,
+            active_path,
+            active_number,
+            active_bytes,
+            index,
+        })
+    }
+
+    /// The directory the segments are stored in.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Flush the newest segment to disk.
+    ///
+    /// Entries are written to the file as they're added, but may not survive a power failure
+    /// until this is called.
+    pub fn sync(&self) -> Result<()> {
+        self.active.sync_data().context(SyncSegment {
+            path: &self.active_path,
+        })
+    }
+
+    fn segment_bytes(&self, segment: usize) -> &[u8] {
+        match self.sealed.get(segment) {
+            Some(mmap) => mmap,
+            None => &self.active_bytes,
+        }
+    }
+
+    // Map the newest segment and start a new one after it.
+    fn seal_active(&mut self) -> Result<()> {
+        self.sync()?;
+
+        // Safe as long as nothing else changes the segment while it's mapped.
+        let mmap = unsafe { Mmap::map(&self.active) }.context(MapSegment {
+            path: &self.active_path,
+        })?;
+
+        let active_number = self.active_number + 1;
+        let active_path = segment_path(&self.dir, active_number);
+        self.active = open_active(&active_path)?;
+
+        self.sealed.push(mmap);
+        self.active_path = active_path;
+        self.active_number = active_number;
+        self.active_bytes.clear();
+
+        Ok(())
+    }
+}
+
+impl EntryStore for FileEntryStore {
+    type Error = Error;
+
+    fn get_last_seq(&self) -> Option<u64> {
+        self.index.keys().next_back().copied()
+    }
+    fn get_entry(&self, seq_num: u64) -> Result<Option<Vec<u8>>> {
+        self.get_entry_ref(seq_num)
+            .map(|entry| entry.map(|entry| entry.to_vec()))
+    }
+    fn get_entry_ref(&self, seq_num: u64) -> Result<Option<&[u8]>> {
+        let result = self.index.get(&seq_num).map(|location| {
+            &self.segment_bytes(location.segment)[location.offset..location.offset + location.len]
+        });
+        Ok(result)
+    }
+    fn get_last_entry(&self) -> Result<Option<Vec<u8>>> {
+        match self.get_last_seq() {
+            Some(seq) => self.get_entry(seq),
+            None => Ok(None),
+        }
+    }
+    fn get_last_entry_ref(&self) -> Result<Option<&[u8]>> {
+        match self.get_last_seq() {
+            Some(seq) => self.get_entry_ref(seq),
+            None => Ok(None),
+        }
+    }
+    fn add_entry(&mut self, entry: &[u8], seq_num: u64) -> Result<()> {
+        ensure!(seq_num != 0, ZeroSeqNum);
+        ensure!(
+            entry.len() <= MAX_ENTRY_SIZE,
+            EntryTooLarge { size: entry.len() }
+        );
+
+        // Don't grow the segment with a copy of what's already there.
+        if self.get_entry_ref(seq_num)? == Some(entry) {
+            return Ok(());
+        }
+
+        let record_len = RECORD_HEADER_SIZE + entry.len();
+        if !self.active_bytes.is_empty()
+            && (self.active_bytes.len() + record_len) as u64 > self.max_segment_size
+        {
+            self.seal_active()?;
+        }
+
+        let mut record = Vec::with_capacity(record_len);
+        record.extend_from_slice(&seq_num.to_le_bytes());
+        record.extend_from_slice(&(entry.len() as u32).to_le_bytes());
+        let checksum = record_checksum(&record, entry);
+        record.extend_from_slice(&checksum.to_le_bytes());
+        record.extend_from_slice(entry);
+
+        if let Err(source) = self.active.write_all(&record) {
+            // Don't leave part of the record behind for the next one to be appended after.
+            let _ = self.active.set_len(self.active_bytes.len() as u64);
+            return Err(source).context(AppendEntry {
+                path: &self.active_path,
+            });
+        }
+
+        self.index.insert(
+            seq_num,
+            Location {
+                segment: self.sealed.len(),
+                offset: self.active_bytes.len() + RECORD_HEADER_SIZE,
+                len: entry.len(),
+            },
+        );
+        self.active_bytes.extend_from_slice(&record);
+
+        Ok(())
+    }
+}
+
+impl Debug for FileEntryStore {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("FileEntryStore")
+            .field("dir", &self.dir)
+            .field("max_segment_size", &self.max_segment_size)
+            .field("segments", &(self.sealed.len() + 1))
+            .field("entries", &self.index.len())
+            .finish()
+    }
+}
+
+fn segment_path(dir: &Path, number: u64) -> PathBuf {
+    dir.join(format!("{:010}.{}", number, SEGMENT_EXTENSION))
+}
+
+// The numbers of the segments in `dir`, oldest first.
+fn segment_numbers(dir: &Path) -> Result<Vec<u64>> {
+    let mut numbers = Vec::new();
+
+    for dir_entry in fs::read_dir(dir).context(ReadDirectory { path: dir })? {
+        let path = dir_entry.context(ReadDirectory { path: dir })?.path();
+
+        if path.extension().and_then(|ext| ext.to_str()) != Some(SEGMENT_EXTENSION) {
+            continue;
+        }
+        if let Some(number) = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .and_then(|stem| stem.parse().ok())
+        {
+            numbers.push(number);
+        }
+    }
+
+    numbers.sort_unstable();
+    Ok(numbers)
+}
+
+// Take an exclusive advisory lock on `dir`, failing if another store holds it.
+fn lock_dir(dir: &Path) -> Result<File> {
+    let path = dir.join(LOCK_FILE_NAME);
+    let lock = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(&path)
+        .context(LockDirectory { path: &path })?;
+    lock.try_lock_exclusive()
+        .context(LockDirectory { path: &path })?;
+
+    Ok(lock)
+}
+
+fn open_active(path: &Path) -> Result<File> {
+    OpenOptions::new()
+        .read(true)
+        .append(true)
+        .create(true)
+        .open(path)
+        .context(OpenSegment { path })
+}
+
+// The checksum of a record, over the seq num and length in `header` and the entry.
+fn record_checksum(header: &[u8], entry: &[u8]) -> u32 {
+    let mut crc = Crc32::new();
+    crc.update(&header[..12]);
+    crc.update(entry);
+    crc.finalize()
+}
+
+// Add the records in `bytes` to `index`, returning the length of the whole records before the
+// first one that's cut off or doesn't match its checksum.
+fn index_segment(bytes: &[u8], segment: usize, index: &mut BTreeMap<u64, Location>) -> usize {
+    let mut offset = 0;
+
+    while let Some(header) = bytes.get(offset..offset + RECORD_HEADER_SIZE) {
+        let seq_num = u64::from_le_bytes(header[..8].try_into().unwrap());
+        let len = u32::from_le_bytes(header[8..12].try_into().unwrap()) as usize;
+        let checksum = u32::from_le_bytes(header[12..].try_into().unwrap());
+        let entry_offset = offset + RECORD_HEADER_SIZE;
+
+        if seq_num == 0 || len > MAX_ENTRY_SIZE || entry_offset + len > bytes.len() {
+            break;
+        }
+        if record_checksum(header, &bytes[entry_offset..entry_offset + len]) != checksum {
+            break;
+        }
+
+        index.insert(
+            seq_num,
+            Location {
+                segment,
+                offset: entry_offset,
+                len,
+            },
+        );
+        offset = entry_offset + len;
+    }
+
+    offset
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Log;
+    use bamboo_rs_core::Keypair;
+    use rand::rngs::OsRng;
+    use tempfile::tempdir;
+
+    fn publish_n(log: &mut Log<FileEntryStore>, n: u64) {
+        (1..=n).for_each(|i| {
+            let payload = format!("message number {}", i);
+            log.publish(payload.as_bytes(), false).unwrap();
+        });
+    }
+
+    fn new_keypair() -> Keypair {
+        let mut csprng: OsRng = OsRng {};
+        Keypair::generate(&mut csprng)
+    }
+
+    #[test]
+    fn reopen_has_the_entries_and_last_seq() {
+        let dir = tempdir().unwrap();
+        let keypair = new_keypair();
+        let keypair_bytes = keypair.to_bytes();
+        let public_key = keypair.public;
+
+        let store = FileEntryStore::open(dir.path()).unwrap();
+        let mut log = Log::new(store, public_key, Some(keypair), 0);
+        publish_n(&mut log, 5);
+        let entries: Vec<_> = (1..=5)
+            .map(|seq_num| log.store.get_entry(seq_num).unwrap().unwrap())
+            .collect();
+        drop(log);
+
+        let store = FileEntryStore::open(dir.path()).unwrap();
+        assert_eq!(store.get_last_seq(), Some(5));
+        for (seq_num, entry) in (1..=5).zip(entries) {
+            assert_eq!(
+                store.get_entry_ref(seq_num).unwrap(),
+                Some(entry.as_slice())
+            );
+        }
+        assert_eq!(store.get_entry_ref(6).unwrap(), None);
+
+        // The reopened log carries on where it left off.
+        let keypair = Keypair::from_bytes(&keypair_bytes).unwrap();
+        let mut log = Log::new(store, public_key, Some(keypair), 0);
+        log.publish(b"message number 6", false).unwrap();
+        assert_eq!(log.store.get_last_seq(), Some(6));
+
+        let mut remote_log = Log::new(MemoryEntryStore::new(), public_key, None, 0);
+        for seq_num in 1..=6 {
+            let entry = log.store.get_entry(seq_num).unwrap().unwrap();
+            remote_log.add(&entry, None).unwrap();
+        }
+    }
+
+    #[test]
+    fn entries_are_split_across_segments() {
+        let dir = tempdir().unwrap();
+        let keypair = new_keypair();
+        let public_key = keypair.public;
+
+        // Small enough that every entry gets its own segment.
+        let store = FileEntryStore::open_with_max_segment_size(dir.path(), 1).unwrap();
+        let mut log = Log::new(store, public_key, Some(keypair), 0);
+        publish_n(&mut log, 3);
+        drop(log);
+
+        assert_eq!(segment_numbers(dir.path()).unwrap(), vec![0, 1, 2]);
+
+        let store = FileEntryStore::open_with_max_segment_size(dir.path(), 1).unwrap();
+        assert_eq!(store.get_last_seq(), Some(3));
+
+        let mut remote_log = Log::new(MemoryEntryStore::new(), public_key, None, 0);
+        for seq_num in 1..=3 {
+            let entry = store.get_entry_ref(seq_num).unwrap().unwrap();
+            remote_log.add(entry, None).unwrap();
+        }
+    }
+
+    #[test]
+    fn open_truncates_a_half_written_record() {
+        let dir = tempdir().unwrap();
+
+        let mut store = FileEntryStore::open(dir.path()).unwrap();
+        store.add_entry(&[1, 2, 3], 1).unwrap();
+        let path = store.active_path.clone();
+        drop(store);
+
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(&2u64.to_le_bytes()).unwrap();
+        drop(file);
+
+        let mut store = FileEntryStore::open(dir.path()).unwrap();
+        assert_eq!(store.get_last_seq(), Some(1));
+
+        store.add_entry(&[4, 5], 2).unwrap();
+        drop(store);
+
+        let store = FileEntryStore::open(dir.path()).unwrap();
+        assert_eq!(store.get_entry_ref(1).unwrap(), Some(&[1, 2, 3][..]));
+        assert_eq!(store.get_entry_ref(2).unwrap(), Some(&[4, 5][..]));
+    }
+
+    #[test]
+    fn open_truncates_at_a_damaged_record() {
+        let dir = tempdir().unwrap();
+
+        let mut store = FileEntryStore::open(dir.path()).unwrap();
+        store.add_entry(&[1, 2, 3], 1).unwrap();
+        store.add_entry(&[4, 5], 2).unwrap();
+        store.add_entry(&[6], 3).unwrap();
+        let path = store.active_path.clone();
+        drop(store);
+
+        // Flip a bit in the second entry.
+        let mut bytes = fs::read(&path).unwrap();
+        let second_entry_offset = 2 * RECORD_HEADER_SIZE + 3;
+        bytes[second_entry_offset] ^= 1;
+        fs::write(&path, &bytes).unwrap();
+
+        let store = FileEntryStore::open(dir.path()).unwrap();
+        assert_eq!(store.get_last_seq(), Some(1));
+        assert_eq!(store.get_entry_ref(2).unwrap(), None);
+        assert_eq!(
+            fs::metadata(&path).unwrap().len(),
+            (RECORD_HEADER_SIZE + 3) as u64
+        );
+    }
+
+    #[test]
+    fn open_errors_on_a_damaged_sealed_segment() {
+        let dir = tempdir().unwrap();
+
+        let mut store = FileEntryStore::open_with_max_segment_size(dir.path(), 1).unwrap();
+        store.add_entry(&[1, 2, 3], 1).unwrap();
+        store.add_entry(&[4, 5], 2).unwrap();
+        drop(store);
+
+        let path = segment_path(dir.path(), 0);
+        let mut bytes = fs::read(&path).unwrap();
+        bytes[RECORD_HEADER_SIZE] ^= 1;
+        fs::write(&path, &bytes).unwrap();
+
+        match FileEntryStore::open_with_max_segment_size(dir.path(), 1) {
+            Err(Error::CorruptSegment { offset: 0, .. }) => {}
+            e => panic!("Expected err, {:?}", e),
+        }
+    }
+
+    #[test]
+    fn adding_the_same_entry_again_does_not_grow_the_segment() {
+        let dir = tempdir().unwrap();
+
+        let mut store = FileEntryStore::open(dir.path()).unwrap();
+        store.add_entry(&[1, 2, 3], 1).unwrap();
+        let len = fs::metadata(&store.active_path).unwrap().len();
+
+        store.add_entry(&[1, 2, 3], 1).unwrap();
+        assert_eq!(fs::metadata(&store.active_path).unwrap().len(), len);
+        assert_eq!(store.active_bytes.len() as u64, len);
+    }
+
+    #[test]
+    fn open_errors_while_the_directory_is_open() {
+        let dir = tempdir().unwrap();
+
+        let store = FileEntryStore::open(dir.path()).unwrap();
+        match FileEntryStore::open(dir.path()) {
+            Err(Error::LockDirectory { .. }) => {}
+            e => panic!("Expected err, {:?}", e),
+        }
+
+        drop(store);
+        FileEntryStore::open(dir.path()).unwrap();
+    }
+
+    #[test]
+    fn entries_can_be_added_out_of_order() {
+        let dir = tempdir().unwrap();
+
+        let mut store = FileEntryStore::open(dir.path()).unwrap();
+        store.add_entry(&[4], 4).unwrap();
+        store.add_entry(&[1], 1).unwrap();
+        store.add_entry(&[2], 1).unwrap();
+        assert_eq!(store.get_last_seq(), Some(4));
+        drop(store);
+
+        let store = FileEntryStore::open(dir.path()).unwrap();
+        assert_eq!(store.get_last_seq(), Some(4));
+        assert_eq!(store.get_entry_ref(1).unwrap(), Some(&[2][..]));
+        assert_eq!(store.get_entry_ref(3).unwrap(), None);
+    }
+
+    #[test]
+    fn add_entry_rejects_seq_num_zero() {
+        let dir = tempdir().unwrap();
+
+        let mut store = FileEntryStore::open(dir.path()).unwrap();
+        match store.add_entry(&[1], 0) {
+            Err(Error::ZeroSeqNum) => {}
+            e => panic!("Expected err, {:?}", e),
+        }
+        assert_eq!(store.get_last_seq(), None);
+    }
+}
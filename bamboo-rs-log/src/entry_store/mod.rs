@@ -1,7 +1,9 @@
+pub mod file_entry_store;
 pub mod memory_entry_store;
 use snafu::AsErrorSource;
 use core::fmt::Debug;
 use core::fmt::Display;
+pub use file_entry_store::FileEntryStore;
 pub use memory_entry_store::*;
 
 pub trait EntryStore {
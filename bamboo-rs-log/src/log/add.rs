@@ -1,10 +1,10 @@
 use core::fmt::Debug;
-use super::Log;
+use super::{Added, Log};
 use crate::entry_store::EntryStore;
 use bamboo_rs_core::entry::decode;
-use bamboo_rs_core::entry::verify;
+use bamboo_rs_core::entry::verify_with_policy;
 use lipmaa_link::lipmaa;
-use snafu::ResultExt;
+use snafu::{ensure, ResultExt};
 
 use super::error::*;
 
@@ -15,29 +15,70 @@ impl<Store: EntryStore + Debug> Log<Store> {
     ///
     /// Typically you would use this when you have an entry published by some other author and you
     /// want to add it to your store. This method does a bunch of checking to make sure the entry
-    /// is legit.
+    /// is legit, including that it belongs to this log's author and log_id and that it meets the
+    /// log's [VerifyPolicy](bamboo_rs_core::entry::VerifyPolicy).
     ///
-    /// Caveat:
-    /// - the lipmaa link that this message references must already exist in the Log. That means if you
-    /// are doing partial replication, you must sort your messages by sequence number and add them
-    /// from oldest to newest.
-    pub fn add(&mut self, entry_bytes: &[u8], payload: Option<&[u8]>) -> Result<(), Error<Store>> {
+    /// Entries can be added in any order. If the entry's lipmaa link or backlink isn't in the
+    /// store yet, the entry's signature is checked and it's held in a pending area. Pending entries
+    /// are verified and stored as soon as the entries they link to are added. See
+    /// [missing_seq_nums](Log::missing_seq_nums) for the entries still needed and
+    /// [set_max_pending](Log::set_max_pending) to limit how many are held.
+    pub fn add(&mut self, entry_bytes: &[u8], payload: Option<&[u8]>) -> Result<Added, Error<Store>> {
         // Decode the entry that we want to add.
         let entry = decode(entry_bytes).context(AddEntryDecodeFailed)?;
 
-        let lipmaa_seq = match lipmaa(entry.seq_num) {
-            0 => 1,
-            n => n,
-        };
+        // Make sure the entry belongs in this log before looking at its links.
+        ensure!(entry.author == self.public_key, AddEntryFromDifferentAuthor);
+        ensure!(
+            entry.log_id == self.log_id,
+            AddEntryWithDifferentLogId {
+                expected: self.log_id,
+                actual: entry.log_id
+            }
+        );
+
+        // Hold entries we can't verify yet, as long as they're signed and allowed by the policy.
+        if let Some(missing) = self.missing_link(entry.seq_num)? {
+            self.verify_policy
+                .check(&entry)
+                .context(AddEntryFailedVerification)?;
+            if self.verify_policy.is_strict() {
+                entry.verify_signature_strict()
+            } else {
+                entry.verify_signature()
+            }
+            .context(AddEntryFailedVerification)?;
+
+            self.hold_pending(entry.seq_num, entry_bytes, payload)?;
+            return Ok(Added::Pending { missing });
+        }
+
+        self.verify_and_store(entry_bytes, payload)?;
+        self.pending.remove(&entry.seq_num);
+
+        let (committed, rejected) = self.commit_pending();
+        Ok(Added::Committed {
+            committed: core::iter::once(entry.seq_num).chain(committed).collect(),
+            rejected,
+        })
+    }
+
+    // Verify an entry against the links in the store and store it.
+    pub(super) fn verify_and_store(
+        &mut self,
+        entry_bytes: &[u8],
+        payload: Option<&[u8]>,
+    ) -> Result<(), Error<Store>> {
+        let entry = decode(entry_bytes).context(AddEntryDecodeFailed)?;
 
         // Get the lipmaa entry.
-        let lipmaa = self.store.get_entry_ref(lipmaa_seq)
+        let lipmaa = self.store.get_entry_ref(lipmaa(entry.seq_num))
             .context(AddEntryGetLipmaaEntry)?;
         // Try and get the backlink entry. If we have it, hash it and check it is correct.
         let backlink = self.store.get_entry_ref(entry.seq_num - 1)
             .context(AddEntryGetBacklinkEntry)?;
 
-        verify(entry_bytes, payload, lipmaa, backlink)
+        verify_with_policy(entry_bytes, payload, lipmaa, backlink, &self.verify_policy)
             .context(AddEntryFailedVerification)?;
 
         //Ok, store it!
@@ -50,27 +91,29 @@ impl<Store: EntryStore + Debug> Log<Store> {
 #[cfg(test)]
 mod tests {
     use crate::entry_store::MemoryEntryStore;
+    use crate::log::{Added, Error};
     use crate::{EntryStore, Log};
     use arrayvec::ArrayVec;
+    use bamboo_rs_core::entry::verify::{Error as VerifyError, VerifyPolicy};
     use bamboo_rs_core::signature::{Signature, ED25519_SIGNATURE_SIZE};
-    use bamboo_rs_core::yamf_hash::{new_blake2b, YamfHash};
-    use bamboo_rs_core::yamf_signatory::YamfSignatory;
-    use bamboo_rs_core::Error;
+    use bamboo_rs_core::yamf_hash::new_blake2b;
     use bamboo_rs_core::{Entry, Keypair};
+    use ed25519_dalek::Signer;
     use rand::rngs::OsRng;
     use std::convert::TryInto;
 
     fn n_valid_entries(n: u64) -> Log<MemoryEntryStore> {
-        let mut csprng: OsRng = OsRng::new().unwrap();
+        let mut csprng: OsRng = OsRng {};
         let keypair: Keypair = Keypair::generate(&mut csprng);
 
         let mut log = Log::new(
             MemoryEntryStore::new(),
             keypair.public.clone(),
             Some(keypair),
+            0,
         );
 
-        (1..n).into_iter().for_each(|i| {
+        (1..=n).for_each(|i| {
             let payload = format!("message number {}", i);
             log.publish(&payload.as_bytes(), false).unwrap();
         });
@@ -78,14 +121,27 @@ mod tests {
         log
     }
 
+    // Sign `entry` with the key pair of `log` and encode it, whatever its links are.
+    fn sign_and_encode(log: &Log<MemoryEntryStore>, mut entry: Entry<&[u8], Vec<u8>>) -> Vec<u8> {
+        let mut unsigned = Vec::new();
+        entry.encode_write(&mut unsigned).unwrap();
+
+        let signature = log.key_pair.as_ref().unwrap().sign(&unsigned);
+        entry.sig = Some(Signature(signature.to_bytes().to_vec()));
+
+        let mut signed = Vec::new();
+        entry.encode_write(&mut signed).unwrap();
+        signed
+    }
+
     #[test]
     fn add_checks_payload_is_correct_length() {
         let remote_log = n_valid_entries(3);
 
         let mut log: Log<MemoryEntryStore> =
-            Log::new(MemoryEntryStore::new(), remote_log.public_key, None);
+            Log::new(MemoryEntryStore::new(), remote_log.public_key, None, 0);
 
-        let mut first_entry: Entry<&[u8], &[u8], &[u8]> = remote_log
+        let mut first_entry: Entry<&[u8], &[u8]> = remote_log
             .store
             .get_entry_ref(1)
             .unwrap()
@@ -95,11 +151,13 @@ mod tests {
 
         first_entry.payload_size = 1; //Set an invalid payload length. Zero tolerance etc ;)
 
-        let entry_bytes: ArrayVec<_> = first_entry.try_into().unwrap();
+        let entry_bytes: ArrayVec<[u8; 512]> = first_entry.try_into().unwrap();
 
         match log.add(&entry_bytes, Some(b"message number 1")) {
-            Err(Error::AddEntryPayloadLengthDidNotMatch) => {}
-            _ => panic!("Expected err"),
+            Err(Error::AddEntryFailedVerification {
+                source: VerifyError::PayloadLengthDidNotMatch { .. },
+            }) => {}
+            e => panic!("Expected err, {:?}", e),
         }
     }
 
@@ -108,82 +166,78 @@ mod tests {
         let remote_log = n_valid_entries(3);
 
         let mut log: Log<MemoryEntryStore> =
-            Log::new(MemoryEntryStore::new(), remote_log.public_key, None);
+            Log::new(MemoryEntryStore::new(), remote_log.public_key, None, 0);
 
         let first_entry = remote_log.store.get_entry(1).unwrap().unwrap();
 
         match log.add(&first_entry, Some(&[0, 1])) {
-            Err(Error::AddEntryPayloadHashDidNotMatch) => {}
-            _ => panic!("Expected err"),
+            Err(Error::AddEntryFailedVerification {
+                source: VerifyError::PayloadHashDidNotMatch {},
+            }) => {}
+            e => panic!("Expected err, {:?}", e),
         }
     }
 
     #[test]
     fn add_checks_entry_not_after_end_of_feed() {
-        let mut csprng: OsRng = OsRng::new().unwrap();
+        let mut csprng: OsRng = OsRng {};
         let keypair: Keypair = Keypair::generate(&mut csprng);
 
         let mut remote_log = Log::new(
             MemoryEntryStore::new(),
             keypair.public.clone(),
             Some(keypair),
+            0,
         );
 
         let payload = format!("message number {}", 1);
         remote_log.publish(&payload.as_bytes(), true).unwrap();
 
-        let first_entry = remote_log.store.get_entry_ref(1).unwrap().unwrap();
-
-        let backlink = new_blake2b(first_entry);
-        let lipmaa_link = new_blake2b(first_entry);
+        let first_entry = remote_log.store.get_entry(1).unwrap().unwrap();
+        let backlink = new_blake2b(&first_entry);
+        let payload_hash = new_blake2b(payload.as_bytes());
 
-        let mut second_entry = Entry::<_, _, &[u8]> {
+        let second_entry = Entry {
+            log_id: 0,
             is_end_of_feed: false,
-            payload_hash: new_blake2b(&payload.as_bytes()),
+            payload_hash: (&payload_hash).into(),
             payload_size: payload.len() as u64,
-            author: YamfSignatory::Ed25519(&remote_log.public_key.as_bytes()[..], None),
+            author: remote_log.public_key,
             seq_num: 2,
-            backlink: Some(backlink),
-            lipmaa_link: Some(lipmaa_link),
+            backlink: Some((&backlink).into()),
+            lipmaa_link: None,
             sig: None,
         };
-
-        let mut second_entry_bytes = Vec::new();
-        second_entry.encode_write(&mut second_entry_bytes).unwrap();
-
-        let signature = remote_log.key_pair.unwrap().sign(&second_entry_bytes);
-        let sig_bytes = &signature.to_bytes()[..];
-        let signature = Signature(sig_bytes.into());
-
-        second_entry.sig = Some(signature);
-
-        let mut second_entry_bytes = Vec::new();
-        second_entry.encode_write(&mut second_entry_bytes).unwrap();
+        let second_entry_bytes = sign_and_encode(&remote_log, second_entry);
 
         let mut log: Log<MemoryEntryStore> =
-            Log::new(MemoryEntryStore::new(), remote_log.public_key, None);
+            Log::new(MemoryEntryStore::new(), remote_log.public_key, None, 0);
 
         log.add(&first_entry, None).unwrap();
 
         match log.add(&second_entry_bytes, None) {
-            Err(Error::AddEntryToFeedThatHasEnded) => {}
-            _ => panic!("Expected err"),
+            Err(Error::AddEntryFailedVerification {
+                source: VerifyError::PublishedAfterEndOfFeed,
+            }) => {}
+            e => panic!("Expected err, {:?}", e),
         }
     }
 
     #[test]
     fn add_needs_lipmaa_link_in_store() {
-        let remote_log = n_valid_entries(3);
+        let remote_log = n_valid_entries(4);
 
         let mut log: Log<MemoryEntryStore> =
-            Log::new(MemoryEntryStore::new(), remote_log.public_key, None);
+            Log::new(MemoryEntryStore::new(), remote_log.public_key, None, 0);
 
-        let second_entry = remote_log.store.get_entry(2).unwrap().unwrap();
+        // The lipmaa link of entry 4 is entry 1, which isn't in the store.
+        let fourth_entry = remote_log.store.get_entry(4).unwrap().unwrap();
 
-        match log.add(&second_entry, None) {
-            Err(Error::AddEntryNoLipmaalinkInStore) => {}
-            _ => panic!("Expected err"),
-        }
+        assert_eq!(
+            log.add(&fourth_entry, None).unwrap(),
+            Added::Pending { missing: 1 }
+        );
+        assert_eq!(log.store.get_last_seq(), None);
     }
 
     #[test]
@@ -191,9 +245,9 @@ mod tests {
         let remote_log = n_valid_entries(3);
 
         let mut log: Log<MemoryEntryStore> =
-            Log::new(MemoryEntryStore::new(), remote_log.public_key, None);
+            Log::new(MemoryEntryStore::new(), remote_log.public_key, None, 0);
 
-        let mut first_entry: Entry<&[u8], &[u8], &[u8]> = remote_log
+        let mut first_entry: Entry<&[u8], &[u8]> = remote_log
             .store
             .get_entry_ref(1)
             .unwrap()
@@ -207,45 +261,47 @@ mod tests {
             link => link,
         };
 
-        let entry_bytes: ArrayVec<_> = first_entry.try_into().unwrap();
+        let entry_bytes: ArrayVec<[u8; 512]> = first_entry.try_into().unwrap();
 
         match log.add(&entry_bytes, None) {
-            Err(Error::AddEntryWithInvalidSignature) => {}
-            _ => panic!("Expected err"),
+            Err(Error::AddEntryFailedVerification {
+                source: VerifyError::InvalidSignature,
+            }) => {}
+            e => panic!("Expected err, {:?}", e),
         }
     }
 
     #[test]
     fn add_checks_lipmaa_link_is_valid() {
-        let remote_log = n_valid_entries(3);
+        let remote_log = n_valid_entries(4);
 
         let mut log: Log<MemoryEntryStore> =
-            Log::new(MemoryEntryStore::new(), remote_log.public_key, None);
+            Log::new(MemoryEntryStore::new(), remote_log.public_key, None, 0);
 
-        let first_entry_bytes = remote_log.store.get_entry(1).unwrap().unwrap();
-        let mut second_entry: Entry<&[u8], &[u8], &[u8]> = remote_log
+        for seq_num in 1..=3 {
+            let entry_bytes = remote_log.store.get_entry(seq_num).unwrap().unwrap();
+            log.add(&entry_bytes, None)
+                .expect("error adding entry, this is not normal");
+        }
+
+        let mut fourth_entry: Entry<&[u8], &[u8]> = remote_log
             .store
-            .get_entry_ref(2)
+            .get_entry_ref(4)
             .unwrap()
             .unwrap()
             .try_into()
             .unwrap();
 
-        log.add(&first_entry_bytes, None)
-            .expect("error adding first entry, this is not normal");
-
         let incorrect_lipmaa = new_blake2b(b"noooo");
+        fourth_entry.lipmaa_link = Some((&incorrect_lipmaa).into());
 
-        second_entry.lipmaa_link = match second_entry.lipmaa_link {
-            Some(YamfHash::Blake2b(_)) => Some(YamfHash::from(&incorrect_lipmaa)),
-            link => link,
-        }; //set the lipmaa link to be zero
-
-        let entry_bytes: ArrayVec<_> = second_entry.try_into().unwrap();
+        let entry_bytes: ArrayVec<[u8; 512]> = fourth_entry.try_into().unwrap();
 
         match log.add(&entry_bytes, None) {
-            Err(Error::AddEntryLipmaaHashDidNotMatch) => {}
-            _ => panic!("Expected err"),
+            Err(Error::AddEntryFailedVerification {
+                source: VerifyError::LipmaaHashDoesNotMatch {},
+            }) => {}
+            e => panic!("Expected err, {:?}", e),
         }
     }
 
@@ -254,11 +310,11 @@ mod tests {
         let remote_log = n_valid_entries(3);
 
         let mut log: Log<MemoryEntryStore> =
-            Log::new(MemoryEntryStore::new(), remote_log.public_key, None);
+            Log::new(MemoryEntryStore::new(), remote_log.public_key, None, 0);
 
         let first_entry_bytes = remote_log.store.get_entry(1).unwrap().unwrap();
 
-        let mut second_entry: Entry<_, _, _> = remote_log
+        let mut second_entry: Entry<&[u8], &[u8]> = remote_log
             .store
             .get_entry_ref(2)
             .unwrap()
@@ -270,118 +326,145 @@ mod tests {
             .expect("error adding first entry, this is not normal");
 
         let incorrect_backlink = new_blake2b(b"noooo");
-        second_entry.backlink = match second_entry.backlink {
-            Some(YamfHash::Blake2b(_)) => Some(YamfHash::from(&incorrect_backlink)),
-            link => link,
-        }; //set the lipmaa link to be zero
+        second_entry.backlink = Some((&incorrect_backlink).into());
 
-        let entry_bytes: ArrayVec<_> = second_entry.try_into().unwrap();
+        let entry_bytes: ArrayVec<[u8; 512]> = second_entry.try_into().unwrap();
 
         match log.add(&entry_bytes, None) {
-            Err(Error::AddEntryBacklinkHashDidNotMatch) => {}
-            _ => panic!("Expected err"),
+            Err(Error::AddEntryFailedVerification {
+                source: VerifyError::BacklinkHashDoesNotMatch {},
+            }) => {}
+            e => panic!("Expected err, {:?}", e),
         }
     }
+
     #[test]
     fn add_checks_lipmaa_link_is_present() {
-        let mut csprng: OsRng = OsRng::new().unwrap();
-        let keypair: Keypair = Keypair::generate(&mut csprng);
-
-        let mut remote_log = Log::new(
-            MemoryEntryStore::new(),
-            keypair.public.clone(),
-            Some(keypair),
-        );
-
-        let payload = format!("message number {}", 1);
-        remote_log.publish(&payload.as_bytes(), false).unwrap();
-
-        let first_entry = remote_log.store.get_entry_ref(1).unwrap().unwrap();
+        let remote_log = n_valid_entries(3);
 
-        let backlink = new_blake2b(first_entry);
+        let third_entry = remote_log.store.get_entry(3).unwrap().unwrap();
+        let backlink = new_blake2b(&third_entry);
+        let payload = "message number 4";
+        let payload_hash = new_blake2b(payload.as_bytes());
 
-        let mut second_entry = Entry::<_, _, &[u8]> {
+        // Entry 4 needs a lipmaa link to entry 1 as well as its backlink.
+        let fourth_entry = Entry {
+            log_id: 0,
             is_end_of_feed: false,
-            payload_hash: new_blake2b(&payload.as_bytes()),
+            payload_hash: (&payload_hash).into(),
             payload_size: payload.len() as u64,
-            author: YamfSignatory::Ed25519(&remote_log.public_key.as_bytes()[..], None),
-            seq_num: 2,
-            backlink: Some(backlink),
+            author: remote_log.public_key,
+            seq_num: 4,
+            backlink: Some((&backlink).into()),
             lipmaa_link: None,
             sig: None,
         };
+        let fourth_entry_bytes = sign_and_encode(&remote_log, fourth_entry);
 
-        let mut second_entry_bytes = Vec::new();
-        second_entry.encode_write(&mut second_entry_bytes).unwrap();
+        let mut log: Log<MemoryEntryStore> =
+            Log::new(MemoryEntryStore::new(), remote_log.public_key, None, 0);
 
-        let signature = remote_log.key_pair.unwrap().sign(&second_entry_bytes);
-        let sig_bytes = &signature.to_bytes()[..];
-        let signature = Signature(sig_bytes.into());
+        for seq_num in 1..=3 {
+            let entry_bytes = remote_log.store.get_entry(seq_num).unwrap().unwrap();
+            log.add(&entry_bytes, None).unwrap();
+        }
 
-        second_entry.sig = Some(signature);
+        match log.add(&fourth_entry_bytes, None) {
+            Err(Error::AddEntryDecodeFailed { .. }) => {}
+            e => panic!("Expected err, {:?}", e),
+        }
+    }
+
+    #[test]
+    fn add_checks_back_link_is_present() {
+        let remote_log = n_valid_entries(1);
+
+        let first_entry = remote_log.store.get_entry(1).unwrap().unwrap();
+        let lipmaa_link = new_blake2b(&first_entry);
+        let payload = "message number 2";
+        let payload_hash = new_blake2b(payload.as_bytes());
 
-        let mut second_entry_bytes = Vec::new();
-        second_entry.encode_write(&mut second_entry_bytes).unwrap();
+        let second_entry = Entry {
+            log_id: 0,
+            is_end_of_feed: false,
+            payload_hash: (&payload_hash).into(),
+            payload_size: payload.len() as u64,
+            author: remote_log.public_key,
+            seq_num: 2,
+            backlink: None,
+            lipmaa_link: Some((&lipmaa_link).into()),
+            sig: None,
+        };
+        let second_entry_bytes = sign_and_encode(&remote_log, second_entry);
 
         let mut log: Log<MemoryEntryStore> =
-            Log::new(MemoryEntryStore::new(), remote_log.public_key, None);
+            Log::new(MemoryEntryStore::new(), remote_log.public_key, None, 0);
 
         log.add(&first_entry, None).unwrap();
 
         match log.add(&second_entry_bytes, None) {
-            Err(Error::AddEntryDecodeFailed) => {}
+            Err(Error::AddEntryDecodeFailed { .. }) => {}
             e => panic!("Expected err, {:?}", e),
         }
     }
-    #[test]
-    fn add_checks_back_link_is_present() {
-        let mut csprng: OsRng = OsRng::new().unwrap();
-        let keypair: Keypair = Keypair::generate(&mut csprng);
 
-        let mut remote_log = Log::new(
-            MemoryEntryStore::new(),
-            keypair.public.clone(),
-            Some(keypair),
-        );
+    #[test]
+    fn add_checks_verify_policy() {
+        let remote_log = n_valid_entries(3);
 
-        let payload = format!("message number {}", 1);
-        remote_log.publish(&payload.as_bytes(), false).unwrap();
+        let mut log: Log<MemoryEntryStore> =
+            Log::new(MemoryEntryStore::new(), remote_log.public_key, None, 0);
+        log.set_verify_policy(VerifyPolicy::new().max_seq_num(2));
 
-        let first_entry = remote_log.store.get_entry_ref(1).unwrap().unwrap();
+        for seq_num in 1..=2 {
+            let entry_bytes = remote_log.store.get_entry(seq_num).unwrap().unwrap();
+            log.add(&entry_bytes, None).unwrap();
+        }
 
-        let lipmaa_link = new_blake2b(first_entry);
+        let third_entry = remote_log.store.get_entry(3).unwrap().unwrap();
+        match log.add(&third_entry, None) {
+            Err(Error::AddEntryFailedVerification {
+                source: VerifyError::SeqNumTooLarge { seq_num: 3, max: 2 },
+            }) => {}
+            e => panic!("Expected err, {:?}", e),
+        }
+        assert_eq!(log.store.get_last_seq(), Some(2));
+    }
 
-        let mut second_entry = Entry::<_, _, &[u8]> {
-            is_end_of_feed: false,
-            payload_hash: new_blake2b(&payload.as_bytes()),
-            payload_size: payload.len() as u64,
-            author: YamfSignatory::Ed25519(&remote_log.public_key.as_ref()[..], None),
-            seq_num: 2,
-            backlink: None,
-            lipmaa_link: Some(lipmaa_link),
-            sig: None,
-        };
+    #[test]
+    fn add_checks_entry_is_by_the_log_author() {
+        let remote_log = n_valid_entries(1);
 
-        let mut second_entry_bytes = Vec::new();
-        second_entry.encode_write(&mut second_entry_bytes).unwrap();
+        let mut csprng: OsRng = OsRng {};
+        let other_keypair: Keypair = Keypair::generate(&mut csprng);
+        let mut log: Log<MemoryEntryStore> =
+            Log::new(MemoryEntryStore::new(), other_keypair.public, None, 0);
 
-        let signature = remote_log.key_pair.unwrap().sign(&second_entry_bytes);
-        let sig_bytes = &signature.to_bytes()[..];
-        let signature = Signature(sig_bytes.into());
+        let first_entry = remote_log.store.get_entry(1).unwrap().unwrap();
 
-        second_entry.sig = Some(signature);
+        match log.add(&first_entry, None) {
+            Err(Error::AddEntryFromDifferentAuthor) => {}
+            e => panic!("Expected err, {:?}", e),
+        }
+        assert_eq!(log.store.get_last_seq(), None);
+    }
 
-        let mut second_entry_bytes = Vec::new();
-        second_entry.encode_write(&mut second_entry_bytes).unwrap();
+    #[test]
+    fn add_checks_entry_is_in_the_log_id() {
+        let remote_log = n_valid_entries(1);
 
         let mut log: Log<MemoryEntryStore> =
-            Log::new(MemoryEntryStore::new(), remote_log.public_key, None);
+            Log::new(MemoryEntryStore::new(), remote_log.public_key, None, 1);
 
-        log.add(&first_entry, None).unwrap();
+        let first_entry = remote_log.store.get_entry(1).unwrap().unwrap();
 
-        match log.add(&second_entry_bytes, None) {
-            Err(Error::AddEntryDecodeFailed) => {}
+        match log.add(&first_entry, None) {
+            Err(Error::AddEntryWithDifferentLogId {
+                expected: 1,
+                actual: 0,
+            }) => {}
             e => panic!("Expected err, {:?}", e),
         }
+        assert_eq!(log.store.get_last_seq(), None);
     }
 }
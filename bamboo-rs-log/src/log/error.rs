@@ -9,10 +9,13 @@ use crate::entry_store::EntryStore;
 #[snafu(visibility = "pub(crate)")]
 pub enum Error<ES: EntryStore + Debug> {
     AddEntryDecodeFailed{source: DecodeError},
+    AddEntryFromDifferentAuthor,
+    AddEntryWithDifferentLogId{expected: u64, actual: u64},
     AddEntryGetLipmaaEntry{source: ES::Error},
     AddEntryGetBacklinkEntry{source: ES::Error},
     AddEntryFailedVerification{source: VerifyError},
     AddEntryFailedToAddEntryToLog{source: ES::Error},
+    AddEntryPendingFull{max_pending: usize},
     PublishEntryGetLipmaaEntry{source: ES::Error},
     PublishEntryGetBacklinkEntry{source: ES::Error},
     PublishNewEntryFailed{source: PublishError},
@@ -1,19 +1,27 @@
 pub use crate::entry_store::EntryStore;
+use bamboo_rs_core::entry::verify::{OwnedVerifyPolicy, VerifyPolicy};
 use bamboo_rs_core::{Keypair, PublicKey};
+use core::ops::RangeInclusive;
+use std::collections::BTreeMap;
 
 pub mod add;
 pub mod publish;
 pub mod error;
+pub mod pending;
 
-pub use add::*;
-pub use publish::*;
 pub use error::*;
+pub use pending::{Added, DEFAULT_MAX_PENDING_ENTRIES};
+
+use pending::PendingEntry;
 
 pub struct Log<Store: EntryStore> {
     pub store: Store,
     pub public_key: PublicKey,
     key_pair: Option<Keypair>,
     log_id: u64,
+    verify_policy: OwnedVerifyPolicy,
+    pending: BTreeMap<u64, PendingEntry>,
+    max_pending: usize,
 }
 
 impl<Store: EntryStore> Log<Store> {
@@ -22,7 +30,20 @@ impl<Store: EntryStore> Log<Store> {
             store,
             public_key,
             key_pair,
-            log_id
+            log_id,
+            verify_policy: VerifyPolicy::new().into_owned(),
+            pending: BTreeMap::new(),
+            max_pending: DEFAULT_MAX_PENDING_ENTRIES,
         }
     }
+
+    /// Set the [VerifyPolicy] that entries passed to [add](Log::add) must meet. Allows every
+    /// valid entry by default.
+    pub fn set_verify_policy<A, L>(&mut self, verify_policy: VerifyPolicy<A, L>)
+    where
+        A: AsRef<[PublicKey]>,
+        L: AsRef<[RangeInclusive<u64>]>,
+    {
+        self.verify_policy = verify_policy.into_owned();
+    }
 }
@@ -0,0 +1,451 @@
+use super::Log;
+use crate::entry_store::EntryStore;
+use core::fmt::Debug;
+use lipmaa_link::lipmaa;
+use snafu::{ensure, ResultExt};
+use std::collections::BTreeSet;
+
+use super::error::*;
+
+/// The default number of entries [Log::add] holds while they wait for their links.
+pub const DEFAULT_MAX_PENDING_ENTRIES: usize = 1024;
+
+/// What happened to an entry passed to [Log::add].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Added {
+    /// The entry was verified and stored.
+    Committed {
+        /// The seq nums stored, starting with the entry that was added and followed by any
+        /// pending entries whose links it provided.
+        committed: Vec<u64>,
+        /// The seq nums of pending entries that were dropped because they failed verification
+        /// once their links arrived.
+        rejected: Vec<u64>,
+    },
+    /// The entry's signature is valid, but an entry it links to isn't in the store yet. It's
+    /// held until its lipmaa link and backlink are both added.
+    Pending {
+        /// The seq num of an entry it's waiting for, its lipmaa link if both are missing.
+        missing: u64,
+    },
+}
+
+pub(super) struct PendingEntry {
+    entry_bytes: Vec<u8>,
+    payload: Option<Vec<u8>>,
+}
+
+impl<Store: EntryStore + Debug> Log<Store> {
+    /// The seq nums of the entries held until their links are added, oldest first.
+    pub fn pending_seq_nums(&self) -> impl Iterator<Item = u64> + '_ {
+        self.pending.keys().copied()
+    }
+
+    /// The seq nums of the entries that pending entries are waiting for, oldest first.
+    ///
+    /// Entries that are themselves pending aren't included, so these are the entries to go and
+    /// fetch.
+    pub fn missing_seq_nums(&self) -> BTreeSet<u64> {
+        self.pending
+            .keys()
+            .flat_map(|seq_num| links(*seq_num))
+            .filter(|seq_num| !self.pending.contains_key(seq_num) && !self.has_entry(*seq_num))
+            .collect()
+    }
+
+    /// The most entries held until their links are added. Defaults to
+    /// [DEFAULT_MAX_PENDING_ENTRIES].
+    pub fn max_pending(&self) -> usize {
+        self.max_pending
+    }
+
+    /// Set the most entries held until their links are added.
+    ///
+    /// If more entries than that are already pending, the newest are dropped.
+    pub fn set_max_pending(&mut self, max_pending: usize) {
+        self.max_pending = max_pending;
+
+        while self.pending.len() > max_pending {
+            let newest = *self.pending.keys().next_back().unwrap();
+            self.pending.remove(&newest);
+        }
+    }
+
+    /// Drop every pending entry.
+    pub fn clear_pending(&mut self) {
+        self.pending.clear();
+    }
+
+    // The seq num of an entry that must be in the store before the entry at `seq_num` can be
+    // verified, if it isn't there yet. Both the lipmaa link and the backlink are needed, otherwise
+    // the entry would be stored without its backlink being checked.
+    pub(super) fn missing_link(&self, seq_num: u64) -> Result<Option<u64>, Error<Store>> {
+        if seq_num <= 1 {
+            return Ok(None);
+        }
+
+        let lipmaa_link = lipmaa(seq_num);
+        let lipmaa_entry = self
+            .store
+            .get_entry_ref(lipmaa_link)
+            .context(AddEntryGetLipmaaEntry)?;
+        if lipmaa_entry.is_none() {
+            return Ok(Some(lipmaa_link));
+        }
+
+        let backlink = seq_num - 1;
+        let backlink_entry = self
+            .store
+            .get_entry_ref(backlink)
+            .context(AddEntryGetBacklinkEntry)?;
+
+        Ok(backlink_entry.map_or(Some(backlink), |_| None))
+    }
+
+    pub(super) fn hold_pending(
+        &mut self,
+        seq_num: u64,
+        entry_bytes: &[u8],
+        payload: Option<&[u8]>,
+    ) -> Result<(), Error<Store>> {
+        ensure!(
+            self.pending.len() < self.max_pending || self.pending.contains_key(&seq_num),
+            AddEntryPendingFull {
+                max_pending: self.max_pending
+            }
+        );
+
+        self.pending.insert(
+            seq_num,
+            PendingEntry {
+                entry_bytes: entry_bytes.to_vec(),
+                payload: payload.map(|payload| payload.to_vec()),
+            },
+        );
+
+        Ok(())
+    }
+
+    // Verify and store every pending entry whose links are now in the store, until there are no
+    // more. Returns the seq nums stored and the seq nums dropped for failing verification.
+    //
+    // If the store fails, the entry stays pending and is retried on the next add.
+    pub(super) fn commit_pending(&mut self) -> (Vec<u64>, Vec<u64>) {
+        let mut committed = Vec::new();
+        let mut rejected = Vec::new();
+
+        loop {
+            let ready = self
+                .pending
+                .keys()
+                .copied()
+                .find(|seq_num| links(*seq_num).all(|link| self.has_entry(link)));
+
+            let seq_num = match ready {
+                Some(seq_num) => seq_num,
+                None => break,
+            };
+
+            let pending = self.pending.remove(&seq_num).unwrap();
+            match self.verify_and_store(&pending.entry_bytes, pending.payload.as_deref()) {
+                Ok(()) => committed.push(seq_num),
+                Err(Error::AddEntryFailedVerification { .. }) => rejected.push(seq_num),
+                Err(_) => {
+                    self.pending.insert(seq_num, pending);
+                    break;
+                }
+            }
+        }
+
+        (committed, rejected)
+    }
+
+    fn has_entry(&self, seq_num: u64) -> bool {
+        matches!(self.store.get_entry_ref(seq_num), Ok(Some(_)))
+    }
+}
+
+// The seq nums of the entries that the entry at `seq_num` links to, its lipmaa link and its
+// backlink.
+fn links(seq_num: u64) -> impl Iterator<Item = u64> {
+    let backlink = seq_num - 1;
+    let lipmaa_link = lipmaa(seq_num);
+
+    core::iter::once(lipmaa_link)
+        .chain(Some(backlink).filter(|backlink| *backlink != lipmaa_link))
+        .filter(|link| *link > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::entry_store::MemoryEntryStore;
+    use crate::log::{Added, Error};
+    use crate::{EntryStore, Log};
+    use arrayvec::ArrayVec;
+    use bamboo_rs_core::entry::verify::Error as VerifyError;
+    use bamboo_rs_core::signature::{Signature, ED25519_SIGNATURE_SIZE};
+    use bamboo_rs_core::{Entry, Keypair};
+    use rand::rngs::OsRng;
+    use snafu::{ensure, Snafu};
+    use std::convert::TryInto;
+
+    fn n_valid_entries(keypair: Keypair, n: u64, message: &str) -> Log<MemoryEntryStore> {
+        let mut log = Log::new(MemoryEntryStore::new(), keypair.public, Some(keypair), 0);
+
+        (1..=n).for_each(|i| {
+            let payload = format!("{} {}", message, i);
+            log.publish(payload.as_bytes(), false).unwrap();
+        });
+
+        log
+    }
+
+    fn new_keypair() -> Keypair {
+        let mut csprng: OsRng = OsRng {};
+        Keypair::generate(&mut csprng)
+    }
+
+    fn empty_log(remote_log: &Log<MemoryEntryStore>) -> Log<MemoryEntryStore> {
+        Log::new(MemoryEntryStore::new(), remote_log.public_key, None, 0)
+    }
+
+    #[test]
+    fn add_commits_pending_entries_when_links_arrive() {
+        let remote_log = n_valid_entries(new_keypair(), 10, "message number");
+        let mut log = empty_log(&remote_log);
+
+        for seq_num in (2..=10).rev() {
+            let entry_bytes = remote_log.store.get_entry(seq_num).unwrap().unwrap();
+            match log.add(&entry_bytes, None) {
+                Ok(Added::Pending { .. }) => {}
+                e => panic!("Expected pending, {:?}", e),
+            }
+        }
+        assert_eq!(log.store.get_last_seq(), None);
+        assert_eq!(log.pending_seq_nums().count(), 9);
+
+        let first_entry = remote_log.store.get_entry(1).unwrap().unwrap();
+        assert_eq!(
+            log.add(&first_entry, None).unwrap(),
+            Added::Committed {
+                committed: (1..=10).collect(),
+                rejected: Vec::new(),
+            }
+        );
+        assert_eq!(log.store.get_last_seq(), Some(10));
+        assert_eq!(log.pending_seq_nums().count(), 0);
+    }
+
+    #[test]
+    fn missing_seq_nums_are_the_entries_to_fetch() {
+        let remote_log = n_valid_entries(new_keypair(), 6, "message number");
+        let mut log = empty_log(&remote_log);
+
+        // Entry 6 links to entry 5, which links to entry 4.
+        for seq_num in 5..=6 {
+            let entry_bytes = remote_log.store.get_entry(seq_num).unwrap().unwrap();
+            log.add(&entry_bytes, None).unwrap();
+        }
+
+        assert_eq!(log.pending_seq_nums().collect::<Vec<_>>(), vec![5, 6]);
+        assert_eq!(
+            log.missing_seq_nums().into_iter().collect::<Vec<_>>(),
+            vec![4]
+        );
+    }
+
+    #[test]
+    fn add_holds_entries_until_their_backlink_arrives() {
+        let remote_log = n_valid_entries(new_keypair(), 4, "message number");
+        let mut log = empty_log(&remote_log);
+
+        for seq_num in 1..=2 {
+            let entry_bytes = remote_log.store.get_entry(seq_num).unwrap().unwrap();
+            log.add(&entry_bytes, None).unwrap();
+        }
+
+        // Entry 4's lipmaa link is entry 1, but it can't be checked against entry 3 yet.
+        let fourth_entry = remote_log.store.get_entry(4).unwrap().unwrap();
+        assert_eq!(
+            log.add(&fourth_entry, None).unwrap(),
+            Added::Pending { missing: 3 }
+        );
+        assert_eq!(
+            log.missing_seq_nums().into_iter().collect::<Vec<_>>(),
+            vec![3]
+        );
+
+        let third_entry = remote_log.store.get_entry(3).unwrap().unwrap();
+        assert_eq!(
+            log.add(&third_entry, None).unwrap(),
+            Added::Committed {
+                committed: vec![3, 4],
+                rejected: Vec::new(),
+            }
+        );
+    }
+
+    #[derive(Debug, Snafu)]
+    enum FlakyError {
+        #[snafu(display("Failed to add entry {}", seq_num))]
+        FailedToAdd { seq_num: u64 },
+    }
+
+    // A memory store that fails to add the entry at `fail_seq_num`.
+    #[derive(Debug)]
+    struct FlakyEntryStore {
+        entries: MemoryEntryStore,
+        fail_seq_num: Option<u64>,
+    }
+
+    impl EntryStore for FlakyEntryStore {
+        type Error = FlakyError;
+
+        fn get_last_seq(&self) -> Option<u64> {
+            self.entries.get_last_seq()
+        }
+        fn get_entry(&self, seq_num: u64) -> Result<Option<Vec<u8>>, FlakyError> {
+            self.entries.get_entry(seq_num).map_err(|err| match err {})
+        }
+        fn get_entry_ref(&self, seq_num: u64) -> Result<Option<&[u8]>, FlakyError> {
+            self.entries
+                .get_entry_ref(seq_num)
+                .map_err(|err| match err {})
+        }
+        fn get_last_entry(&self) -> Result<Option<Vec<u8>>, FlakyError> {
+            self.entries.get_last_entry().map_err(|err| match err {})
+        }
+        fn get_last_entry_ref(&self) -> Result<Option<&[u8]>, FlakyError> {
+            self.entries
+                .get_last_entry_ref()
+                .map_err(|err| match err {})
+        }
+        fn add_entry(&mut self, entry: &[u8], seq_num: u64) -> Result<(), FlakyError> {
+            ensure!(self.fail_seq_num != Some(seq_num), FailedToAdd { seq_num });
+            self.entries
+                .add_entry(entry, seq_num)
+                .map_err(|err| match err {})
+        }
+    }
+
+    #[test]
+    fn pending_entries_stay_pending_when_the_store_fails() {
+        let remote_log = n_valid_entries(new_keypair(), 2, "message number");
+        let store = FlakyEntryStore {
+            entries: MemoryEntryStore::new(),
+            fail_seq_num: Some(2),
+        };
+        let mut log = Log::new(store, remote_log.public_key, None, 0);
+
+        let second_entry = remote_log.store.get_entry(2).unwrap().unwrap();
+        log.add(&second_entry, None).unwrap();
+
+        let first_entry = remote_log.store.get_entry(1).unwrap().unwrap();
+        assert_eq!(
+            log.add(&first_entry, None).unwrap(),
+            Added::Committed {
+                committed: vec![1],
+                rejected: Vec::new(),
+            }
+        );
+        assert_eq!(log.pending_seq_nums().collect::<Vec<_>>(), vec![2]);
+
+        log.store.fail_seq_num = None;
+        assert_eq!(
+            log.add(&second_entry, None).unwrap(),
+            Added::Committed {
+                committed: vec![2],
+                rejected: Vec::new(),
+            }
+        );
+        assert_eq!(log.pending_seq_nums().count(), 0);
+    }
+
+    #[test]
+    fn add_checks_signature_before_holding_entries() {
+        let remote_log = n_valid_entries(new_keypair(), 4, "message number");
+        let mut log = empty_log(&remote_log);
+
+        let mut fourth_entry: Entry<&[u8], &[u8]> = remote_log
+            .store
+            .get_entry_ref(4)
+            .unwrap()
+            .unwrap()
+            .try_into()
+            .unwrap();
+
+        let incorrect_sig_bytes = [0u8; ED25519_SIGNATURE_SIZE];
+        fourth_entry.sig = Some(Signature(&incorrect_sig_bytes));
+
+        let entry_bytes: ArrayVec<[u8; 512]> = fourth_entry.try_into().unwrap();
+
+        match log.add(&entry_bytes, None) {
+            Err(Error::AddEntryFailedVerification {
+                source: VerifyError::InvalidSignature,
+            }) => {}
+            e => panic!("Expected err, {:?}", e),
+        }
+        assert_eq!(log.pending_seq_nums().count(), 0);
+    }
+
+    #[test]
+    fn add_errors_when_pending_is_full() {
+        let remote_log = n_valid_entries(new_keypair(), 4, "message number");
+        let mut log = empty_log(&remote_log);
+        log.set_max_pending(1);
+
+        let fourth_entry = remote_log.store.get_entry(4).unwrap().unwrap();
+        log.add(&fourth_entry, None).unwrap();
+
+        let third_entry = remote_log.store.get_entry(3).unwrap().unwrap();
+        match log.add(&third_entry, None) {
+            Err(Error::AddEntryPendingFull { max_pending: 1 }) => {}
+            e => panic!("Expected err, {:?}", e),
+        }
+
+        // Adding an entry that's already pending again doesn't need more room.
+        log.add(&fourth_entry, None).unwrap();
+        assert_eq!(log.pending_seq_nums().collect::<Vec<_>>(), vec![4]);
+    }
+
+    #[test]
+    fn set_max_pending_drops_the_newest_entries() {
+        let remote_log = n_valid_entries(new_keypair(), 4, "message number");
+        let mut log = empty_log(&remote_log);
+
+        for seq_num in 3..=4 {
+            let entry_bytes = remote_log.store.get_entry(seq_num).unwrap().unwrap();
+            log.add(&entry_bytes, None).unwrap();
+        }
+
+        log.set_max_pending(1);
+        assert_eq!(log.max_pending(), 1);
+        assert_eq!(log.pending_seq_nums().collect::<Vec<_>>(), vec![3]);
+    }
+
+    #[test]
+    fn pending_entries_that_fail_verification_are_rejected() {
+        let keypair = new_keypair();
+        let other_keypair = Keypair::from_bytes(&keypair.to_bytes()).unwrap();
+
+        // Two different logs by the same author, so their second entries link to different
+        // first entries.
+        let remote_log = n_valid_entries(keypair, 2, "message number");
+        let forked_log = n_valid_entries(other_keypair, 2, "forked message number");
+        let mut log = empty_log(&remote_log);
+
+        let forked_second_entry = forked_log.store.get_entry(2).unwrap().unwrap();
+        log.add(&forked_second_entry, None).unwrap();
+
+        let first_entry = remote_log.store.get_entry(1).unwrap().unwrap();
+        assert_eq!(
+            log.add(&first_entry, None).unwrap(),
+            Added::Committed {
+                committed: vec![1],
+                rejected: vec![2],
+            }
+        );
+        assert_eq!(log.store.get_last_seq(), Some(1));
+        assert_eq!(log.pending_seq_nums().count(), 0);
+    }
+}
@@ -52,37 +52,40 @@ mod tests {
     use crate::EntryStore;
     use bamboo_rs_core::entry::decode;
     use bamboo_rs_core::{Keypair};
+    use bamboo_rs_core::entry::publish::Error as PublishError;
 
     use rand::rngs::OsRng;
 
     #[test]
     fn publish_and_verify_signature() {
-        let mut csprng: OsRng = OsRng::new().unwrap();
+        let mut csprng: OsRng = OsRng {};
         let keypair: Keypair = Keypair::generate(&mut csprng);
 
         let mut log = Log::new(
             MemoryEntryStore::new(),
             keypair.public.clone(),
             Some(keypair),
+            0,
         );
         let payload = [1, 2, 3];
         log.publish(&payload, false).unwrap();
 
         let entry_bytes = log.store.get_entry_ref(1).unwrap().unwrap();
 
-        let mut entry = decode(entry_bytes).unwrap();
-        assert!(entry.verify_signature().unwrap());
+        let entry = decode(entry_bytes).unwrap();
+        assert!(entry.verify_signature().is_ok());
     }
 
     #[test]
     fn publish_after_an_end_of_feed_message_errors() {
-        let mut csprng: OsRng = OsRng::new().unwrap();
+        let mut csprng: OsRng = OsRng {};
         let keypair: Keypair = Keypair::generate(&mut csprng);
 
         let mut log = Log::new(
             MemoryEntryStore::new(),
             keypair.public.clone(),
             Some(keypair),
+            0,
         );
         let payload = [1, 2, 3];
 
@@ -90,21 +93,23 @@ mod tests {
         log.publish(&payload, true).unwrap();
 
         match log.publish(&payload, false) {
-            Err(Error::PublishNewEntryFailed) => {}
-            _ => panic!("expected publish to fail with an error"),
+            Err(Error::PublishNewEntryFailed {
+                source: PublishError::PublishAfterEndOfFeed,
+            }) => {}
+            e => panic!("expected publish to fail with an error, got: {:?}", e),
         }
     }
 
     #[test]
     fn publish_without_secret_key_errors() {
-        let mut csprng: OsRng = OsRng::new().unwrap();
+        let mut csprng: OsRng = OsRng {};
         let keypair: Keypair = Keypair::generate(&mut csprng);
 
-        let mut log = Log::new(MemoryEntryStore::new(), keypair.public.clone(), None);
+        let mut log = Log::new(MemoryEntryStore::new(), keypair.public.clone(), None, 0);
         let payload = [1, 2, 3];
 
         match log.publish(&payload, false) {
-            Err(Error::PublishNewEntryFailed) => {}
+            Err(Error::PublishWithoutKeypair) => {}
             e => panic!("expected publish to fail with an error, got: {:?}", e),
         }
     }